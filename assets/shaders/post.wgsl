@@ -0,0 +1,59 @@
+// Put in front of every post-processing shader, which only has to define `fs_main`
+// and its own bindings in group 1
+
+struct PostGlobals {
+    // part of the texture the viewport covers in uv, min then max
+    rect: vec4<f32>,
+    // size of the whole texture in pixels
+    size: vec2<f32>,
+    // seconds since the renderer started
+    time: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // position in the whole texture
+    @location(0) uv: vec2<f32>,
+    // 0 to 1 across the viewport, (0, 0) at the top left
+    @location(1) local: vec2<f32>,
+}
+
+// output of the previous pass
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> globals: PostGlobals;
+// what the effect started from, the same as `source` in its first pass
+@group(0) @binding(3)
+var original: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.uv = uv;
+    out.local = (uv - globals.rect.xy) / (globals.rect.zw - globals.rect.xy);
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    return out;
+}
+
+// size of a pixel in uv
+fn texel() -> vec2<f32> {
+    return 1.0 / globals.size;
+}
+
+// keeps samples inside the viewport so effects don't pick up the ones next to it
+fn clamp_uv(uv: vec2<f32>) -> vec2<f32> {
+    let half = texel() * 0.5;
+    return clamp(uv, globals.rect.xy + half, globals.rect.zw - half);
+}
+
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(source, source_sampler, clamp_uv(uv), 0.0);
+}
+
+fn sample_original(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(original, source_sampler, clamp_uv(uv), 0.0);
+}
@@ -0,0 +1,10 @@
+// x: brightness where bloom starts, y: intensity, z: radius in pixels
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let scene = sample_original(in.uv);
+    let bloom = sample_source(in.uv).rgb * params.y;
+    return vec4<f32>(scene.rgb + bloom, scene.a);
+}
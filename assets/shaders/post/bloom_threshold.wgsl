@@ -0,0 +1,11 @@
+// x: brightness where bloom starts, y: intensity, z: radius in pixels
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample_source(in.uv);
+    let brightness = max(color.r, max(color.g, color.b));
+    let contribution = max(brightness - params.x, 0.0) / max(brightness, 0.0001);
+    return vec4<f32>(color.rgb * contribution, 1.0);
+}
@@ -0,0 +1,18 @@
+// `DIRECTION` and `RADIUS`, the component of `params` holding the
+// radius in pixels, get defined in front of this
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+
+const WEIGHTS = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var weights = WEIGHTS;
+    let step = DIRECTION * texel() * params[RADIUS] / 4.0;
+    var color = sample_source(in.uv) * weights[0];
+    for (var i = 1; i < 5; i++) {
+        let offset = step * f32(i);
+        color += (sample_source(in.uv + offset) + sample_source(in.uv - offset)) * weights[i];
+    }
+    return color;
+}
@@ -0,0 +1,12 @@
+// x: how far red and blue split at the edges, in pixels
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = (in.local - 0.5) * 2.0 * params.x * texel();
+    let color = sample_source(in.uv);
+    let red = sample_source(in.uv + offset).r;
+    let blue = sample_source(in.uv - offset).b;
+    return vec4<f32>(red, color.g, blue, color.a);
+}
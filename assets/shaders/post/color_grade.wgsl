@@ -0,0 +1,32 @@
+// x: how much of the graded color to use, y: colors per channel in the lut
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+// slices of blue side by side, red goes right and green down in each
+@group(1) @binding(1)
+var lut: texture_2d<f32>;
+@group(1) @binding(2)
+var lut_sampler: sampler;
+
+// luts are made from colors as they're stored in images
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample_source(in.uv);
+    let size = params.y;
+    let srgb = clamp(to_srgb(color.rgb), vec3<f32>(0.0), vec3<f32>(1.0));
+
+    let blue = srgb.b * (size - 1.0);
+    let slice = floor(blue);
+    let next = min(slice + 1.0, size - 1.0);
+    let inside = (srgb.rg * (size - 1.0) + 0.5) / vec2<f32>(size * size, size);
+    let low = textureSampleLevel(lut, lut_sampler, inside + vec2<f32>(slice / size, 0.0), 0.0);
+    let high = textureSampleLevel(lut, lut_sampler, inside + vec2<f32>(next / size, 0.0), 0.0);
+    let graded = mix(low.rgb, high.rgb, blue - slice);
+
+    return vec4<f32>(mix(color.rgb, graded, params.x), color.a);
+}
@@ -0,0 +1,22 @@
+// x: scanline intensity, y: curvature, z: scanline height in pixels
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+
+const TAU: f32 = 6.28318531;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // bulge the picture out like a tube
+    var centered = in.local * 2.0 - 1.0;
+    centered *= 1.0 + params.y * dot(centered.yx, centered.yx) * 0.25;
+    let local = centered * 0.5 + 0.5;
+    if any(local < vec2<f32>(0.0)) || any(local > vec2<f32>(1.0)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let uv = mix(globals.rect.xy, globals.rect.zw, local);
+    let color = sample_source(uv);
+    let row = uv.y * globals.size.y;
+    let scanline = 0.5 + 0.5 * cos(row / max(params.z, 1.0) * TAU);
+    return vec4<f32>(color.rgb * (1.0 - params.x * (1.0 - scanline)), color.a);
+}
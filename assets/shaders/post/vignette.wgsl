@@ -0,0 +1,12 @@
+// x: intensity, y: radius the darkening starts at, z: softness
+@group(1) @binding(0)
+var<uniform> params: vec4<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = sample_source(in.uv);
+    // 0 in the center, 1 in the corners
+    let distance = length(in.local - 0.5) * 1.41421356;
+    let darkness = smoothstep(params.y, params.y + params.z, distance) * params.x;
+    return vec4<f32>(color.rgb * (1.0 - darkness), color.a);
+}
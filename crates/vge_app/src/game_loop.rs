@@ -0,0 +1,56 @@
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use crate::{App, Ctx};
+
+/// Upper bound on steps run to catch up after a stall, the rest of
+/// the backlog is dropped so a slow step can't snowball
+const MAX_CATCH_UP_STEPS: u32 = 8;
+
+/// Calls [`App::step`] at the tick rate of `ctx` and [`App::draw`] once
+/// for every signal on `redraw`, returns when `redraw` disconnects
+pub fn run(app: &mut impl App, ctx: &mut Ctx, redraw: mpsc::Receiver<()>) {
+    let mut previous = Instant::now();
+    let mut accumulator = Duration::ZERO;
+
+    loop {
+        let tick = ctx.tick_duration();
+
+        let draw = match redraw.recv_timeout(tick.saturating_sub(accumulator)) {
+            Ok(()) => {
+                // only the latest frame matters if the window got ahead of us
+                while redraw.try_recv().is_ok() {}
+                true
+            }
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+
+        let now = Instant::now();
+        accumulator += now - previous;
+        previous = now;
+
+        let mut steps = 0;
+        while accumulator >= ctx.tick_duration() {
+            accumulator -= ctx.tick_duration();
+
+            ctx.poll_input();
+            app.step(ctx);
+            ctx.frame += 1;
+
+            steps += 1;
+            if steps == MAX_CATCH_UP_STEPS {
+                accumulator = Duration::ZERO;
+                break;
+            }
+        }
+
+        if draw {
+            let alpha = accumulator.as_secs_f32() / ctx.tick_duration().as_secs_f32();
+            app.draw(ctx, alpha.min(1.0));
+            ctx.present();
+        }
+    }
+}
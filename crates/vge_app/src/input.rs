@@ -0,0 +1,421 @@
+use std::collections::{HashMap, HashSet};
+
+use vge_math::Vec2;
+
+/// Physical key, the position of the key on a US layout keyboard
+/// regardless of what the current layout maps it to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    KeyA,
+    KeyB,
+    KeyC,
+    KeyD,
+    KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Escape,
+    Enter,
+    Tab,
+    Backspace,
+    Space,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    ShiftLeft,
+    ShiftRight,
+    ControlLeft,
+    ControlRight,
+    AltLeft,
+    AltRight,
+    SuperLeft,
+    SuperRight,
+    CapsLock,
+    Minus,
+    Equal,
+    BracketLeft,
+    BracketRight,
+    Backslash,
+    Semicolon,
+    Quote,
+    Backquote,
+    Comma,
+    Period,
+    Slash,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+}
+
+/// Logical keys without a printable character
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NamedKey {
+    Escape,
+    Enter,
+    Tab,
+    Backspace,
+    Space,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Shift,
+    Control,
+    Alt,
+    Super,
+    CapsLock,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+}
+
+/// Logical key, what the key produces with the current keyboard layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// Always lowercase so shift doesn't change which key is held
+    Character(char),
+    Named(NamedKey),
+}
+
+impl Key {
+    pub fn character(c: char) -> Self {
+        Self::Character(c.to_lowercase().next().unwrap_or(c))
+    }
+}
+
+impl From<char> for Key {
+    fn from(value: char) -> Self {
+        Self::character(value)
+    }
+}
+
+impl From<NamedKey> for Key {
+    fn from(value: NamedKey) -> Self {
+        Self::Named(value)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u16),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Keys {
+    physical: HashSet<KeyCode>,
+    logical: HashSet<Key>,
+}
+
+impl Keys {
+    fn insert(&mut self, code: Option<KeyCode>, key: Option<Key>) {
+        self.physical.extend(code);
+        self.logical.extend(key);
+    }
+
+    fn remove(&mut self, code: Option<KeyCode>, key: Option<Key>) {
+        if let Some(code) = code {
+            self.physical.remove(&code);
+        }
+        if let Some(key) = key {
+            self.logical.remove(&key);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.physical.clear();
+        self.logical.clear();
+    }
+}
+
+/// Anything a key can be looked up by, either a [`KeyCode`] or a [`Key`]
+pub trait KeyLookup {
+    fn is_in(&self, keys: &Keys) -> bool;
+}
+
+impl KeyLookup for KeyCode {
+    fn is_in(&self, keys: &Keys) -> bool {
+        keys.physical.contains(self)
+    }
+}
+
+impl KeyLookup for Key {
+    fn is_in(&self, keys: &Keys) -> bool {
+        keys.logical.contains(self)
+    }
+}
+
+impl KeyLookup for NamedKey {
+    fn is_in(&self, keys: &Keys) -> bool {
+        Key::Named(*self).is_in(keys)
+    }
+}
+
+impl KeyLookup for char {
+    fn is_in(&self, keys: &Keys) -> bool {
+        Key::character(*self).is_in(keys)
+    }
+}
+
+/// Input as seen by a single step
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    keys_down: Keys,
+    keys_pressed: Keys,
+    keys_released: Keys,
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    cursor: Vec2,
+    scroll: Vec2,
+    window_size: (u32, u32),
+}
+
+impl Input {
+    /// Held down during this step
+    pub fn key_down(&self, key: impl KeyLookup) -> bool {
+        key.is_in(&self.keys_down)
+    }
+
+    /// Went down since the previous step
+    pub fn key_pressed(&self, key: impl KeyLookup) -> bool {
+        key.is_in(&self.keys_pressed)
+    }
+
+    /// Went up since the previous step
+    pub fn key_released(&self, key: impl KeyLookup) -> bool {
+        key.is_in(&self.keys_released)
+    }
+
+    pub fn mouse_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Cursor position in window pixels, origin at the top left
+    pub fn mouse_position(&self) -> Vec2 {
+        self.cursor
+    }
+
+    /// Scroll since the previous step in lines, positive y scrolls up
+    pub fn mouse_wheel(&self) -> Vec2 {
+        self.scroll
+    }
+
+    pub fn window_size(&self) -> (u32, u32) {
+        self.window_size
+    }
+}
+
+/// Collects input from the window thread until the next step takes a snapshot
+#[derive(Debug, Default)]
+pub struct InputState {
+    current: Input,
+    /// Logical key each held physical key went down as, releases can report a
+    /// different one when modifiers change in between, like shift+1 and 1
+    held: HashMap<KeyCode, Key>,
+}
+
+impl InputState {
+    pub fn key(&mut self, code: Option<KeyCode>, key: Option<Key>, pressed: bool) {
+        let input = &mut self.current;
+        if pressed {
+            if let (Some(code), Some(key)) = (code, key) {
+                self.held.insert(code, key);
+            }
+            input.keys_down.insert(code, key);
+            input.keys_pressed.insert(code, key);
+        } else {
+            let key = code.and_then(|code| self.held.remove(&code)).or(key);
+            // still down while another physical key holds it, like the other shift
+            let key = key.filter(|key| !self.held.values().any(|held| held == key));
+            input.keys_down.remove(code, key);
+            input.keys_released.insert(code, key);
+        }
+    }
+
+    pub fn mouse_button(&mut self, button: MouseButton, pressed: bool) {
+        let input = &mut self.current;
+        if pressed {
+            input.buttons_down.insert(button);
+            input.buttons_pressed.insert(button);
+        } else {
+            input.buttons_down.remove(&button);
+            input.buttons_released.insert(button);
+        }
+    }
+
+    pub fn cursor_moved(&mut self, position: Vec2) {
+        self.current.cursor = position;
+    }
+
+    pub fn scroll(&mut self, delta: Vec2) {
+        self.current.scroll += delta;
+    }
+
+    pub fn resized(&mut self, width: u32, height: u32) {
+        self.current.window_size = (width, height);
+    }
+
+    /// Releases everything, the window won't tell us about keys let go while unfocused
+    pub fn focus_lost(&mut self) {
+        let input = &mut self.current;
+        self.held.clear();
+        for code in input.keys_down.physical.drain() {
+            input.keys_released.physical.insert(code);
+        }
+        for key in input.keys_down.logical.drain() {
+            input.keys_released.logical.insert(key);
+        }
+        for button in input.buttons_down.drain() {
+            input.buttons_released.insert(button);
+        }
+    }
+
+    /// Input for the next step, presses and releases are only reported once
+    pub fn snapshot(&mut self) -> Input {
+        let input = self.current.clone();
+
+        self.current.keys_pressed.clear();
+        self.current.keys_released.clear();
+        self.current.buttons_pressed.clear();
+        self.current.buttons_released.clear();
+        self.current.scroll = Vec2::ZERO;
+
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_uses_the_key_from_the_press() {
+        let mut state = InputState::default();
+        state.key(Some(KeyCode::ShiftLeft), Some(NamedKey::Shift.into()), true);
+        state.key(Some(KeyCode::Digit1), Some('!'.into()), true);
+        state.key(
+            Some(KeyCode::ShiftLeft),
+            Some(NamedKey::Shift.into()),
+            false,
+        );
+        assert!(state.snapshot().key_down('!'));
+
+        state.key(Some(KeyCode::Digit1), Some('1'.into()), false);
+        let input = state.snapshot();
+        assert!(!input.key_down('!'));
+        assert!(input.key_released('!'));
+        assert!(!input.key_released('1'));
+    }
+
+    #[test]
+    fn shift_stays_down_while_either_is_held() {
+        let mut state = InputState::default();
+        state.key(Some(KeyCode::ShiftLeft), Some(NamedKey::Shift.into()), true);
+        state.key(
+            Some(KeyCode::ShiftRight),
+            Some(NamedKey::Shift.into()),
+            true,
+        );
+        state.key(
+            Some(KeyCode::ShiftLeft),
+            Some(NamedKey::Shift.into()),
+            false,
+        );
+        assert!(state.snapshot().key_down(NamedKey::Shift));
+
+        state.key(
+            Some(KeyCode::ShiftRight),
+            Some(NamedKey::Shift.into()),
+            false,
+        );
+        assert!(!state.snapshot().key_down(NamedKey::Shift));
+    }
+}
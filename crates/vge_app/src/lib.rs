@@ -1,10 +1,27 @@
-use thiserror::Error;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
 
-use std::{path::PathBuf, str::FromStr, sync::mpsc};
+use input::{Input, InputState, KeyLookup, MouseButton};
 
-use vge_render::{Gfx, mesh};
+use vge_math::Vec2;
+use vge_render::{
+    Camera2D, Color, Gfx, Painter, TargetDraw, Viewport,
+    mesh::{DrawSprite, Sprite, Text},
+};
+
+pub mod game_loop;
+pub mod input;
 
 pub mod options {
+    use std::path::PathBuf;
+
+    use vge_render::{PresentMode, image::RgbaImage};
+
+    use crate::DEFAULT_TICK_RATE;
+
     #[derive(Default)]
     pub enum Window {
         #[default]
@@ -17,25 +34,337 @@ pub mod options {
         Wgpu,
     }
 
-    #[derive(Default)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum Fullscreen {
+        #[default]
+        Windowed,
+        /// Window covering the whole monitor
+        Borderless,
+        /// Takes over the monitor using its largest video mode
+        Exclusive,
+    }
+
+    pub struct WindowConfig {
+        pub title: String,
+        /// Logical size of the window contents
+        pub size: (u32, u32),
+        pub min_size: Option<(u32, u32)>,
+        pub max_size: Option<(u32, u32)>,
+        pub resizable: bool,
+        pub decorations: bool,
+        pub fullscreen: Fullscreen,
+        pub present_mode: PresentMode,
+        pub icon: Option<RgbaImage>,
+        pub visible: bool,
+    }
+
+    impl Default for WindowConfig {
+        fn default() -> Self {
+            Self {
+                title: "vge".into(),
+                size: (640, 480),
+                min_size: None,
+                max_size: None,
+                resizable: true,
+                decorations: true,
+                fullscreen: Fullscreen::Windowed,
+                present_mode: PresentMode::Vsync,
+                icon: None,
+                visible: true,
+            }
+        }
+    }
+
     pub struct Options {
         pub window: Window,
         pub renderer: Renderer,
+        pub window_config: WindowConfig,
+        /// Initial [`Ctx::tick_rate`](crate::Ctx::tick_rate)
+        pub tick_rate: u32,
+        /// Directory [`Assets`](vge_render::Assets) loads from
+        pub asset_root: PathBuf,
+        /// See [`Gfx::set_hot_reload`](vge_render::Gfx::set_hot_reload)
+        pub hot_reload: bool,
+        /// See [`Gfx::set_pixel_perfect`](vge_render::Gfx::set_pixel_perfect)
+        pub pixel_perfect: Option<(u32, u32)>,
     }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Self {
+                window: Window::default(),
+                renderer: Renderer::default(),
+                window_config: WindowConfig::default(),
+                tick_rate: DEFAULT_TICK_RATE,
+                asset_root: PathBuf::new(),
+                hot_reload: false,
+                pixel_perfect: None,
+            }
+        }
+    }
+}
+
+/// Messages sent from the game thread to the window thread
+pub enum Command {
+    DrawSprite(DrawSprite),
+    DrawShapes(Painter),
+    DrawText(Text),
+    DrawToTarget(TargetDraw),
+    SetClearColor(Color),
+    /// Clear color of the frame being drawn only
+    SetFrameClearColor(Color),
+    SetPixelPerfect(Option<(u32, u32)>),
+    SetViewports(Vec<Viewport>),
+    /// Saves the next frame rendered
+    Screenshot(PathBuf),
+    StartRecording(PathBuf, u32),
+    StopRecording,
+    SetTitle(String),
+    SetVisible(bool),
+    Resize(u32, u32),
+    Quit,
+    /// Marks the end of a frame, everything drawn since the previous
+    /// `Present` replaces what is currently on screen
+    Present,
 }
 
+pub const DEFAULT_TICK_RATE: u32 = 60;
+
 #[derive(Clone)]
 pub struct Ctx {
-    sender: mpsc::Sender<i32>,
+    sender: mpsc::Sender<Command>,
+    input_state: Arc<Mutex<InputState>>,
+    input: Input,
+    viewports: Vec<Viewport>,
+    pixel_perfect: Option<(u32, u32)>,
+    painter: Painter,
+    tick_rate: u32,
+    frame: u64,
 }
 
 impl Ctx {
-    pub fn new(sender: mpsc::Sender<i32>) -> Self {
-        Self { sender }
+    pub fn new(sender: mpsc::Sender<Command>, input_state: Arc<Mutex<InputState>>) -> Self {
+        Self {
+            sender,
+            input_state,
+            input: Input::default(),
+            viewports: vec![Viewport::default()],
+            pixel_perfect: None,
+            painter: Painter::new(),
+            tick_rate: DEFAULT_TICK_RATE,
+            frame: 0,
+        }
+    }
+
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Held down during this step, takes either a [`KeyCode`](input::KeyCode)
+    /// or a logical [`Key`](input::Key)
+    pub fn key_down(&self, key: impl KeyLookup) -> bool {
+        self.input.key_down(key)
+    }
+
+    /// Went down since the previous step
+    pub fn key_pressed(&self, key: impl KeyLookup) -> bool {
+        self.input.key_pressed(key)
+    }
+
+    /// Went up since the previous step
+    pub fn key_released(&self, key: impl KeyLookup) -> bool {
+        self.input.key_released(key)
+    }
+
+    pub fn mouse_down(&self, button: MouseButton) -> bool {
+        self.input.mouse_down(button)
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.input.mouse_pressed(button)
+    }
+
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.input.mouse_released(button)
+    }
+
+    /// Cursor position in window pixels, origin at the top left
+    pub fn mouse_position(&self) -> Vec2 {
+        self.input.mouse_position()
+    }
+
+    /// Cursor position in world space as seen through the first viewport
+    pub fn mouse_world(&self) -> Vec2 {
+        let (width, height) = self.input.window_size();
+        let mut position = self.mouse_position();
+        let mut target_size = Vec2::new(width as f32, height as f32);
+        if let Some(resolution) = self.pixel_perfect {
+            // from the window to the scaled up frame inside it
+            let rect = vge_render::upscale::screen_rect((width, height), resolution);
+            target_size = Vec2::new(resolution.0 as f32, resolution.1 as f32);
+            position = (position - rect.min) / rect.size() * target_size;
+        }
+        self.viewports
+            .first()
+            .map(|viewport| viewport.screen_to_world(position, target_size))
+            .unwrap_or_default()
+    }
+
+    /// Scroll since the previous step in lines, positive y scrolls up
+    pub fn mouse_wheel(&self) -> Vec2 {
+        self.input.mouse_wheel()
+    }
+
+    /// Number of times [`App::step`] is called per second
+    pub fn tick_rate(&self) -> u32 {
+        self.tick_rate
+    }
+
+    /// Changes the step rate, takes effect from the next step
+    pub fn set_tick_rate(&mut self, tick_rate: u32) {
+        self.tick_rate = tick_rate.max(1);
+    }
+
+    /// Seconds between two steps
+    pub fn delta(&self) -> f32 {
+        1.0 / self.tick_rate as f32
+    }
+
+    /// Number of steps run so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub(crate) fn tick_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.tick_rate as f64)
+    }
+
+    pub(crate) fn poll_input(&mut self) {
+        // a panicked window thread can't send any more input anyway
+        if let Ok(mut state) = self.input_state.lock() {
+            self.input = state.snapshot();
+        }
+    }
+
+    pub fn draw_sprite(&mut self, sprite: &Sprite, position: Vec2) {
+        self.send(Command::DrawSprite(sprite.at(position)));
+    }
+
+    /// Draws with scale, rotation, tint etc. set on the [`DrawSprite`]
+    pub fn draw_sprite_ext(&mut self, sprite: DrawSprite) {
+        self.send(Command::DrawSprite(sprite));
+    }
+
+    /// Builds the text with [`Font::text`](vge_render::Font::text)
+    pub fn draw_text(&mut self, text: Text) {
+        self.send(Command::DrawText(text));
+    }
+
+    /// Draws into a [`RenderTarget`](vge_render::RenderTarget) before the rest of the frame
+    pub fn draw_to_target(&mut self, draw: TargetDraw) {
+        self.send(Command::DrawToTarget(draw));
+    }
+
+    /// Immediate-mode shape drawing, whatever gets painted is sent along on [`Ctx::present`]
+    pub fn painter(&mut self) -> &mut Painter {
+        &mut self.painter
+    }
+
+    pub fn set_clear_color(&mut self, color: Color) {
+        self.send(Command::SetClearColor(color));
+    }
+
+    /// Clears only the frame being drawn to `color`, later ones go back to [`Ctx::set_clear_color`]
+    pub fn set_frame_clear_color(&mut self, color: Color) {
+        self.send(Command::SetFrameClearColor(color));
+    }
+
+    /// Saves the next frame rendered to `path`, see [`Gfx::save_screenshot`]
+    pub fn screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.send(Command::Screenshot(path.into()));
+    }
+
+    /// Saves every frame from now on into `dir`, see [`Gfx::start_recording`]
+    pub fn start_recording(&mut self, dir: impl Into<PathBuf>, fps: u32) {
+        self.send(Command::StartRecording(dir.into(), fps));
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.send(Command::StopRecording);
+    }
+
+    /// Takes the viewports and pixel-perfect resolution from `gfx`, for when they
+    /// were set on it directly, like in [`App::init`]
+    pub fn sync_with(&mut self, gfx: &Gfx) {
+        self.viewports = gfx.viewports().to_vec();
+        self.pixel_perfect = gfx.pixel_perfect();
+    }
+
+    /// See [`Gfx::set_pixel_perfect`]
+    pub fn set_pixel_perfect(&mut self, resolution: Option<(u32, u32)>) {
+        self.pixel_perfect = resolution;
+        self.send(Command::SetPixelPerfect(resolution));
+    }
+
+    pub fn pixel_perfect(&self) -> Option<(u32, u32)> {
+        self.pixel_perfect
+    }
+
+    /// Draws through a single camera covering the whole window
+    pub fn set_camera(&mut self, camera: Camera2D) {
+        self.set_viewports(vec![Viewport::new(camera)]);
+    }
+
+    /// Draws everything once per viewport, for split-screen or minimaps
+    pub fn set_viewports(&mut self, viewports: Vec<Viewport>) {
+        self.viewports = viewports.clone();
+        self.send(Command::SetViewports(viewports));
+    }
+
+    pub fn viewports(&self) -> &[Viewport] {
+        &self.viewports
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.send(Command::SetTitle(title.into()));
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.send(Command::SetVisible(visible));
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.send(Command::Resize(width, height));
+    }
+
+    pub fn quit(&mut self) {
+        self.send(Command::Quit);
+    }
+
+    pub fn present(&mut self) {
+        if !self.painter.is_empty() {
+            let painter = std::mem::take(&mut self.painter);
+            self.send(Command::DrawShapes(painter));
+        }
+        self.send(Command::Present);
+    }
+
+    fn send(&self, command: Command) {
+        // the window is gone once the receiver is dropped, nothing left to draw to
+        let _ = self.sender.send(command);
     }
 }
 
 pub trait App: Send + Sync + 'static {
     fn init(&mut self, ctx: &mut Ctx, gfx: &mut Gfx);
+
+    /// Game logic, called [`Ctx::tick_rate`] times per second
     fn step(&mut self, ctx: &mut Ctx);
+
+    /// Called once per displayed frame after any pending steps,
+    /// `alpha` is how far along we are towards the next step (0..1)
+    /// for interpolating between the previous and current state
+    fn draw(&mut self, ctx: &mut Ctx, alpha: f32) {
+        let _ = (ctx, alpha);
+    }
 }
@@ -0,0 +1,393 @@
+use crate::{Circle, Ray, Vec2};
+
+/// Where a ray hit a shape
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub point: Vec2,
+    /// Surface normal at `point`, facing the ray
+    pub normal: Vec2,
+    /// Distance along the ray, see [`Ray::at`]
+    pub time: f32,
+}
+
+/// How two overlapping shapes collide, moving the second shape
+/// by `normal * depth` separates them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Manifold {
+    /// Points from the first shape towards the second
+    pub normal: Vec2,
+    pub depth: f32,
+    contacts: [Vec2; 2],
+    contact_count: usize,
+}
+
+impl Manifold {
+    fn new(normal: Vec2, depth: f32) -> Self {
+        Self {
+            normal,
+            depth,
+            contacts: [Vec2::ZERO; 2],
+            contact_count: 0,
+        }
+    }
+
+    fn push_contact(&mut self, point: Vec2) {
+        if self.contact_count < self.contacts.len() {
+            self.contacts[self.contact_count] = point;
+            self.contact_count += 1;
+        }
+    }
+
+    /// One or two points where the shapes touch
+    pub fn contacts(&self) -> &[Vec2] {
+        &self.contacts[..self.contact_count]
+    }
+
+    /// Same collision seen from the other shape
+    pub fn flip(self) -> Self {
+        Self {
+            normal: -self.normal,
+            ..self
+        }
+    }
+}
+
+pub(crate) fn closest_point_on_segment(start: Vec2, end: Vec2, point: Vec2) -> Vec2 {
+    let edge = end - start;
+    let len_sq = edge.length_squared();
+    if len_sq <= f32::EPSILON {
+        return start;
+    }
+
+    let t = ((point - start).dot(edge) / len_sq).clamp(0.0, 1.0);
+    start + edge * t
+}
+
+/// Hit reported for rays starting inside a shape
+pub(crate) fn inside_hit(ray: &Ray) -> RayHit {
+    RayHit {
+        point: ray.origin,
+        normal: -ray.direction,
+        time: 0.0,
+    }
+}
+
+/// Outward facing normal of the edge starting at `index`, zero for degenerate edges
+fn edge_normal(points: &[Vec2], index: usize) -> Vec2 {
+    let start = points[index];
+    let end = points[(index + 1) % points.len()];
+    let edge = end - start;
+    Vec2::new(edge.y, -edge.x).normalize_or_zero()
+}
+
+/// Cyrus-Beck clipping of the ray against every edge of a counter-clockwise convex polygon
+pub(crate) fn ray_convex(ray: &Ray, points: &[Vec2]) -> Option<RayHit> {
+    if points.len() < 3 || ray.direction == Vec2::ZERO {
+        return None;
+    }
+
+    let mut enter = 0.0;
+    let mut exit = f32::INFINITY;
+    let mut normal = None;
+
+    for (i, point) in points.iter().enumerate() {
+        let edge_normal = edge_normal(points, i);
+        if edge_normal == Vec2::ZERO {
+            continue;
+        }
+
+        let denom = edge_normal.dot(ray.direction);
+        let distance = edge_normal.dot(*point - ray.origin);
+        if denom.abs() <= f32::EPSILON {
+            // parallel to the edge, either always inside of it or never
+            if distance < 0.0 {
+                return None;
+            }
+            continue;
+        }
+
+        let time = distance / denom;
+        if denom < 0.0 {
+            if time > enter {
+                enter = time;
+                normal = Some(edge_normal);
+            }
+        } else if time < exit {
+            exit = time;
+        }
+
+        if enter > exit {
+            return None;
+        }
+    }
+
+    Some(match normal {
+        Some(normal) => RayHit {
+            point: ray.at(enter),
+            normal,
+            time: enter,
+        },
+        None => inside_hit(ray),
+    })
+}
+
+pub(crate) fn circle_circle(a: &Circle, b: &Circle) -> Option<Manifold> {
+    let offset = b.center - a.center;
+    let radius = a.radius + b.radius;
+    let dist_sq = offset.length_squared();
+    if dist_sq >= radius * radius {
+        return None;
+    }
+
+    let dist = dist_sq.sqrt();
+    let normal = if dist > 0.0 { offset / dist } else { Vec2::Y };
+    let depth = radius - dist;
+
+    let mut manifold = Manifold::new(normal, depth);
+    manifold.push_contact(a.center + normal * (a.radius - depth * 0.5));
+    Some(manifold)
+}
+
+/// Normal points from the polygon towards the circle
+pub(crate) fn convex_circle(points: &[Vec2], circle: &Circle) -> Option<Manifold> {
+    if points.is_empty() {
+        return None;
+    }
+
+    let mut separation = f32::MIN;
+    let mut face = 0;
+    for (i, point) in points.iter().enumerate() {
+        let s = edge_normal(points, i).dot(circle.center - *point);
+        if s > separation {
+            separation = s;
+            face = i;
+        }
+    }
+
+    if separation >= circle.radius {
+        return None;
+    }
+
+    let normal = edge_normal(points, face);
+    if separation <= 0.0 {
+        // center is inside, push out through the closest face
+        let mut manifold = Manifold::new(normal, circle.radius - separation);
+        manifold.push_contact(circle.center - normal * separation);
+        return Some(manifold);
+    }
+
+    let start = points[face];
+    let end = points[(face + 1) % points.len()];
+    let closest = closest_point_on_segment(start, end, circle.center);
+    let offset = circle.center - closest;
+    let dist_sq = offset.length_squared();
+    if dist_sq >= circle.radius * circle.radius {
+        return None;
+    }
+
+    let dist = dist_sq.sqrt();
+    let mut manifold = Manifold::new(offset.normalize_or(normal), circle.radius - dist);
+    manifold.push_contact(closest);
+    Some(manifold)
+}
+
+/// Deepest separation of `b` along the edge normals of `a` and the edge it belongs to
+fn max_separation(a: &[Vec2], b: &[Vec2]) -> (f32, usize) {
+    let mut best = (f32::MIN, 0);
+    for (i, point) in a.iter().enumerate() {
+        let normal = edge_normal(a, i);
+        if normal == Vec2::ZERO {
+            continue;
+        }
+
+        let separation = b
+            .iter()
+            .map(|other| normal.dot(*other - *point))
+            .fold(f32::MAX, f32::min);
+        if separation > best.0 {
+            best = (separation, i);
+        }
+    }
+    best
+}
+
+/// Keeps the part of the segment behind the plane, interpolating a new end point if it crosses
+fn clip_segment(segment: [Vec2; 2], normal: Vec2, offset: f32) -> Option<[Vec2; 2]> {
+    let d0 = normal.dot(segment[0]) - offset;
+    let d1 = normal.dot(segment[1]) - offset;
+
+    match (d0 <= 0.0, d1 <= 0.0) {
+        (true, true) => Some(segment),
+        (false, false) => None,
+        (inside0, _) => {
+            let crossing = segment[0].lerp(segment[1], d0 / (d0 - d1));
+            Some(if inside0 {
+                [segment[0], crossing]
+            } else {
+                [crossing, segment[1]]
+            })
+        }
+    }
+}
+
+/// Separating axis test between two counter-clockwise convex polygons,
+/// contacts come from clipping the incident edge against the reference edge
+pub(crate) fn convex_convex(a: &[Vec2], b: &[Vec2]) -> Option<Manifold> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+
+    let (separation_a, edge_a) = max_separation(a, b);
+    if separation_a >= 0.0 {
+        return None;
+    }
+    let (separation_b, edge_b) = max_separation(b, a);
+    if separation_b >= 0.0 {
+        return None;
+    }
+
+    // prefer the first shape when both axes are about as good, keeps the normal stable
+    let flip = separation_b > separation_a * 0.98 + 0.001;
+    let (reference, incident, face, separation) = if flip {
+        (b, a, edge_b, separation_b)
+    } else {
+        (a, b, edge_a, separation_a)
+    };
+
+    let ref_normal = edge_normal(reference, face);
+    let ref_start = reference[face];
+    let ref_end = reference[(face + 1) % reference.len()];
+
+    let incident_edge = (0..incident.len())
+        .min_by(|&i, &j| {
+            let di = edge_normal(incident, i).dot(ref_normal);
+            let dj = edge_normal(incident, j).dot(ref_normal);
+            di.total_cmp(&dj)
+        })
+        .unwrap_or(0);
+    let incident_segment = [
+        incident[incident_edge],
+        incident[(incident_edge + 1) % incident.len()],
+    ];
+
+    let tangent = (ref_end - ref_start).normalize_or_zero();
+    let clipped = clip_segment(incident_segment, -tangent, -tangent.dot(ref_start))
+        .and_then(|segment| clip_segment(segment, tangent, tangent.dot(ref_end)));
+
+    let normal = if flip { -ref_normal } else { ref_normal };
+    let mut manifold = Manifold::new(normal, -separation);
+    if let Some(segment) = clipped {
+        for point in segment {
+            if ref_normal.dot(point - ref_start) <= 0.0 {
+                manifold.push_contact(point);
+            }
+        }
+    }
+
+    if manifold.contacts().is_empty() {
+        // degenerate edges clipped everything away, fall back to the deepest point
+        let deepest = incident
+            .iter()
+            .copied()
+            .min_by(|p, q| {
+                ref_normal
+                    .dot(*p - ref_start)
+                    .total_cmp(&ref_normal.dot(*q - ref_start))
+            })
+            .unwrap_or(ref_start);
+        manifold.push_contact(deepest);
+    }
+
+    Some(manifold)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Polygon, Rect};
+
+    fn square(min: Vec2, size: f32) -> [Vec2; 4] {
+        Rect::new(min, min + Vec2::splat(size)).corners()
+    }
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    #[test]
+    fn convex_convex_overlapping_squares() {
+        let a = square(Vec2::ZERO, 2.0);
+        let b = square(Vec2::new(1.5, 0.5), 2.0);
+        let manifold = convex_convex(&a, &b).unwrap();
+
+        assert!(close(manifold.normal, Vec2::X));
+        assert!((manifold.depth - 0.5).abs() < 1e-4);
+        assert_eq!(manifold.contacts().len(), 2);
+        for contact in manifold.contacts() {
+            assert!((1.5..=2.0).contains(&contact.x), "{contact:?}");
+            assert!((0.5..=2.0).contains(&contact.y), "{contact:?}");
+        }
+    }
+
+    #[test]
+    fn convex_convex_normal_points_from_first_to_second() {
+        // the big box's faces make the better axis, so the shapes get swapped internally
+        let small = square(Vec2::new(-0.5, 1.8), 1.0);
+        let big = square(Vec2::new(-5.0, -3.0), 5.0);
+        let manifold = convex_convex(&small, &big).unwrap();
+        assert!(close(manifold.normal, -Vec2::Y));
+        assert!((manifold.depth - 0.2).abs() < 1e-4);
+
+        let manifold = convex_convex(&big, &small).unwrap();
+        assert!(close(manifold.normal, Vec2::Y));
+    }
+
+    #[test]
+    fn convex_convex_separated_or_touching() {
+        let a = square(Vec2::ZERO, 1.0);
+        assert!(convex_convex(&a, &square(Vec2::new(3.0, 0.0), 1.0)).is_none());
+        assert!(convex_convex(&a, &square(Vec2::new(1.0, 0.0), 1.0)).is_none());
+        assert!(convex_convex(&a, &[]).is_none());
+    }
+
+    #[test]
+    fn convex_convex_rotated() {
+        let diamond = Polygon::regular(Vec2::new(1.5, 0.5), 0.75, 4);
+        let manifold = convex_convex(&square(Vec2::ZERO, 1.0), diamond.points()).unwrap();
+        assert!(close(manifold.normal, Vec2::X));
+        assert!((manifold.depth - 0.25).abs() < 1e-4);
+        assert!(close(manifold.contacts()[0], Vec2::new(0.75, 0.5)));
+    }
+
+    #[test]
+    fn ray_convex_hits_nearest_edge() {
+        let points = square(Vec2::ZERO, 2.0);
+        let hit = ray_convex(&Ray::new(Vec2::new(-5.0, 1.0), Vec2::X), &points).unwrap();
+        assert!(close(hit.point, Vec2::new(0.0, 1.0)));
+        assert!(close(hit.normal, -Vec2::X));
+        assert!((hit.time - 5.0).abs() < 1e-4);
+
+        let hit = ray_convex(&Ray::new(Vec2::new(3.0, 3.0), -Vec2::ONE), &points).unwrap();
+        assert!(close(hit.point, Vec2::splat(2.0)));
+    }
+
+    #[test]
+    fn ray_convex_misses() {
+        let points = square(Vec2::ZERO, 2.0);
+        // pointing away
+        assert!(ray_convex(&Ray::new(Vec2::new(-5.0, 1.0), -Vec2::X), &points).is_none());
+        // passing by
+        assert!(ray_convex(&Ray::new(Vec2::new(-5.0, 3.0), Vec2::X), &points).is_none());
+        // parallel to an edge, outside of it
+        assert!(ray_convex(&Ray::new(Vec2::new(-1.0, -1.0), Vec2::Y), &points).is_none());
+        assert!(ray_convex(&Ray::new(Vec2::ZERO, Vec2::ZERO), &points).is_none());
+        assert!(ray_convex(&Ray::new(Vec2::ZERO, Vec2::X), &points[..2]).is_none());
+    }
+
+    #[test]
+    fn ray_convex_from_inside() {
+        let points = square(Vec2::ZERO, 2.0);
+        let ray = Ray::new(Vec2::ONE, Vec2::X);
+        assert_eq!(ray_convex(&ray, &points), Some(inside_hit(&ray)));
+    }
+}
@@ -0,0 +1,154 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::Vec2;
+
+/// Integer vector for tile coordinates and other signed grid positions
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Zeroable, Pod)]
+pub struct IVec2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl_vec!(IVec2, i32, 2, { x, y });
+impl_vec_signed!(IVec2, { x, y });
+
+impl IVec2 {
+    pub const X: Self = Self::new(1, 0);
+    pub const Y: Self = Self::new(0, 1);
+
+    #[inline]
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// Rotated 90 degrees counter-clockwise
+    #[inline]
+    pub fn perp(&self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Steps along the grid between the two points without moving diagonally
+    #[inline]
+    pub fn manhattan_distance(&self, rhs: Self) -> u32 {
+        self.x.abs_diff(rhs.x) + self.y.abs_diff(rhs.y)
+    }
+
+    /// Rounds towards negative infinity, unlike `/` which rounds towards zero
+    #[inline]
+    pub fn div_euclid(&self, rhs: Self) -> Self {
+        Self::new(self.x.div_euclid(rhs.x), self.y.div_euclid(rhs.y))
+    }
+
+    /// Always positive, handy for wrapping negative tile coordinates into a chunk
+    #[inline]
+    pub fn rem_euclid(&self, rhs: Self) -> Self {
+        Self::new(self.x.rem_euclid(rhs.x), self.y.rem_euclid(rhs.y))
+    }
+
+    #[inline]
+    pub fn yx(&self) -> Self {
+        Self::new(self.y, self.x)
+    }
+
+    #[inline]
+    pub fn as_vec2(&self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32)
+    }
+
+    /// Negative components wrap around
+    #[inline]
+    pub fn as_uvec2(&self) -> UVec2 {
+        UVec2::new(self.x as u32, self.y as u32)
+    }
+}
+
+/// Unsigned integer vector for pixel coordinates and sizes
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Zeroable, Pod)]
+pub struct UVec2 {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl_vec!(UVec2, u32, 2, { x, y });
+
+impl UVec2 {
+    pub const X: Self = Self::new(1, 0);
+    pub const Y: Self = Self::new(0, 1);
+
+    #[inline]
+    pub const fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    /// Width times height when used as a size
+    #[inline]
+    pub fn area(&self) -> u32 {
+        self.x * self.y
+    }
+
+    #[inline]
+    pub fn yx(&self) -> Self {
+        Self::new(self.y, self.x)
+    }
+
+    #[inline]
+    pub fn as_vec2(&self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32)
+    }
+
+    /// Components above `i32::MAX` wrap around
+    #[inline]
+    pub fn as_ivec2(&self) -> IVec2 {
+        IVec2::new(self.x as i32, self.y as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators() {
+        let a = IVec2::new(7, -7);
+        let b = IVec2::new(2, 3);
+        assert_eq!(a + b, IVec2::new(9, -4));
+        assert_eq!(a * 2, IVec2::new(14, -14));
+        assert_eq!(a / b, IVec2::new(3, -2));
+        assert_eq!(a % b, IVec2::new(1, -1));
+        assert_eq!(-a, IVec2::new(-7, 7));
+        assert_eq!(a.abs(), IVec2::new(7, 7));
+        assert_eq!(IVec2::new(0, -5).signum(), IVec2::new(0, -1));
+
+        // euclidean division rounds down instead of towards zero
+        assert_eq!(a.div_euclid(b), IVec2::new(3, -3));
+        assert_eq!(a.rem_euclid(b), IVec2::new(1, 2));
+
+        assert_eq!(UVec2::new(3, 4) * UVec2::new(2, 2), UVec2::new(6, 8));
+        assert_eq!(UVec2::new(3, 4).area(), 12);
+        assert_eq!(UVec2::new(3, 4).max_element(), 4);
+    }
+
+    #[test]
+    fn grid_helpers() {
+        let v = IVec2::new(3, -1);
+        assert_eq!(v.perp(), IVec2::new(1, 3));
+        assert_eq!(v.perp().perp(), -v);
+        assert_eq!(v.manhattan_distance(IVec2::new(-1, 2)), 7);
+        assert_eq!(v.yx(), IVec2::new(-1, 3));
+        assert_eq!(UVec2::new(1, 2).yx(), UVec2::new(2, 1));
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(IVec2::new(-2, 5).as_vec2(), Vec2::new(-2.0, 5.0));
+        assert_eq!(IVec2::new(-1, 5).as_uvec2(), UVec2::new(u32::MAX, 5));
+        assert_eq!(UVec2::new(4, 5).as_vec2(), Vec2::new(4.0, 5.0));
+        assert_eq!(UVec2::new(u32::MAX, 5).as_ivec2(), IVec2::new(-1, 5));
+
+        assert_eq!(IVec2::from([1, 2]), IVec2::new(1, 2));
+        assert_eq!(<(u32, u32)>::from(UVec2::new(1, 2)), (1, 2));
+        assert_eq!(<[i32; 2]>::from(IVec2::Y), [0, 1]);
+    }
+}
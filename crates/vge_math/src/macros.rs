@@ -0,0 +1,281 @@
+/// Component-wise arithmetic, conversions and helpers shared by every vector type
+macro_rules! impl_vec {
+    ($ty:ident, $scalar:ty, $n:literal, { $($field:ident),+ }) => {
+        impl $ty {
+            pub const ZERO: Self = Self::splat(0 as $scalar);
+            pub const ONE: Self = Self::splat(1 as $scalar);
+
+            #[inline]
+            pub const fn splat(v: $scalar) -> Self {
+                Self { $($field: v),+ }
+            }
+
+            #[inline]
+            pub fn min(&self, rhs: Self) -> Self {
+                Self { $($field: self.$field.min(rhs.$field)),+ }
+            }
+
+            #[inline]
+            pub fn max(&self, rhs: Self) -> Self {
+                Self { $($field: self.$field.max(rhs.$field)),+ }
+            }
+
+            /// Component-wise clamp, `min` must not be greater than `max`
+            #[inline]
+            pub fn clamp(&self, min: Self, max: Self) -> Self {
+                self.max(min).min(max)
+            }
+
+            #[inline]
+            pub fn min_element(&self) -> $scalar {
+                let mut min = <$scalar>::MAX;
+                $(min = min.min(self.$field);)+
+                min
+            }
+
+            #[inline]
+            pub fn max_element(&self) -> $scalar {
+                let mut max = <$scalar>::MIN;
+                $(max = max.max(self.$field);)+
+                max
+            }
+
+            #[inline]
+            pub fn to_array(&self) -> [$scalar; $n] {
+                [$(self.$field),+]
+            }
+        }
+
+        impl_vec!(@op $ty, $scalar, { $($field),+ }, Add, add, AddAssign, add_assign, +);
+        impl_vec!(@op $ty, $scalar, { $($field),+ }, Sub, sub, SubAssign, sub_assign, -);
+        impl_vec!(@op $ty, $scalar, { $($field),+ }, Mul, mul, MulAssign, mul_assign, *);
+        impl_vec!(@op $ty, $scalar, { $($field),+ }, Div, div, DivAssign, div_assign, /);
+        impl_vec!(@op $ty, $scalar, { $($field),+ }, Rem, rem, RemAssign, rem_assign, %);
+
+        impl std::iter::Sum for $ty {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, |acc, v| acc + v)
+            }
+        }
+
+        impl From<[$scalar; $n]> for $ty {
+            fn from([$($field),+]: [$scalar; $n]) -> Self {
+                Self { $($field),+ }
+            }
+        }
+
+        impl From<$ty> for [$scalar; $n] {
+            fn from(value: $ty) -> Self {
+                value.to_array()
+            }
+        }
+
+        impl From<impl_vec!(@tuple $scalar, { $($field),+ })> for $ty {
+            fn from(($($field),+): impl_vec!(@tuple $scalar, { $($field),+ })) -> Self {
+                Self { $($field),+ }
+            }
+        }
+
+        impl From<$ty> for impl_vec!(@tuple $scalar, { $($field),+ }) {
+            fn from(value: $ty) -> Self {
+                ($(value.$field),+)
+            }
+        }
+    };
+
+    (@tuple $scalar:ty, { $($field:ident),+ }) => {
+        ($(impl_vec!(@scalar $scalar, $field)),+)
+    };
+
+    (@scalar $scalar:ty, $field:ident) => {
+        $scalar
+    };
+
+    (@op $ty:ident, $scalar:ty, { $($field:ident),+ },
+        $trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:tt) => {
+        impl std::ops::$trait for $ty {
+            type Output = $ty;
+
+            fn $fn(self, rhs: Self) -> Self::Output {
+                Self { $($field: self.$field $op rhs.$field),+ }
+            }
+        }
+
+        impl std::ops::$trait<$scalar> for $ty {
+            type Output = $ty;
+
+            fn $fn(self, rhs: $scalar) -> Self::Output {
+                Self { $($field: self.$field $op rhs),+ }
+            }
+        }
+
+        impl std::ops::$trait<$ty> for $scalar {
+            type Output = $ty;
+
+            fn $fn(self, rhs: $ty) -> Self::Output {
+                $ty { $($field: self $op rhs.$field),+ }
+            }
+        }
+
+        impl std::ops::$assign_trait for $ty {
+            fn $assign_fn(&mut self, rhs: Self) {
+                $(self.$field = self.$field $op rhs.$field;)+
+            }
+        }
+
+        impl std::ops::$assign_trait<$scalar> for $ty {
+            fn $assign_fn(&mut self, rhs: $scalar) {
+                $(self.$field = self.$field $op rhs;)+
+            }
+        }
+    };
+}
+
+/// Negation and absolute values for signed vector types
+macro_rules! impl_vec_signed {
+    ($ty:ident, { $($field:ident),+ }) => {
+        impl $ty {
+            #[inline]
+            pub fn abs(&self) -> Self {
+                Self { $($field: self.$field.abs()),+ }
+            }
+
+            /// -1, 0 or 1 per component, zero stays zero
+            #[inline]
+            pub fn signum(&self) -> Self {
+                Self { $($field: if self.$field == Default::default() {
+                    self.$field
+                } else {
+                    self.$field.signum()
+                }),+ }
+            }
+        }
+
+        impl std::ops::Neg for $ty {
+            type Output = $ty;
+
+            fn neg(self) -> Self::Output {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+    };
+}
+
+/// Methods only floating point vectors have
+macro_rules! impl_vec_float {
+    ($ty:ident, { $($field:ident),+ }) => {
+        impl $ty {
+            #[inline]
+            pub fn dot(&self, rhs: Self) -> f32 {
+                0.0 $(+ self.$field * rhs.$field)+
+            }
+
+            #[inline]
+            pub fn length_squared(&self) -> f32 {
+                self.dot(*self)
+            }
+
+            #[inline]
+            pub fn length(&self) -> f32 {
+                self.length_squared().sqrt()
+            }
+
+            #[inline]
+            pub fn distance(&self, rhs: Self) -> f32 {
+                (*self - rhs).length()
+            }
+
+            #[inline]
+            pub fn distance_squared(&self, rhs: Self) -> f32 {
+                (*self - rhs).length_squared()
+            }
+
+            /// Returns `self` at `t` of 0 and `rhs` at `t` of 1
+            #[inline]
+            pub fn lerp(&self, rhs: Self, t: f32) -> Self {
+                *self + (rhs - *self) * t
+            }
+
+            /// Non-finite for zero length vectors, see [`Self::normalize_or_zero`]
+            #[inline]
+            pub fn normalize(&self) -> Self {
+                *self / self.length()
+            }
+
+            #[inline]
+            pub fn normalize_or_zero(&self) -> Self {
+                self.normalize_or(Self::ZERO)
+            }
+
+            #[inline]
+            pub fn normalize_or(&self, fallback: Self) -> Self {
+                let len = self.length();
+                if len > 0.0 && len.is_finite() {
+                    *self / len
+                } else {
+                    fallback
+                }
+            }
+
+            /// Shortens the vector to `max` if it is longer
+            #[inline]
+            pub fn clamp_length_max(&self, max: f32) -> Self {
+                let len_sq = self.length_squared();
+                if len_sq > max * max {
+                    *self * (max / len_sq.sqrt())
+                } else {
+                    *self
+                }
+            }
+
+            /// Reflects off a surface with the normalized `normal`
+            #[inline]
+            pub fn reflect(&self, normal: Self) -> Self {
+                *self - normal * (2.0 * self.dot(normal))
+            }
+
+            /// Projection of `self` onto `onto`, which does not need to be normalized
+            #[inline]
+            pub fn project(&self, onto: Self) -> Self {
+                onto * (self.dot(onto) / onto.length_squared())
+            }
+
+            /// What is left of `self` after removing its projection onto `onto`
+            #[inline]
+            pub fn reject(&self, onto: Self) -> Self {
+                *self - self.project(onto)
+            }
+
+            #[inline]
+            pub fn floor(&self) -> Self {
+                Self { $($field: self.$field.floor()),+ }
+            }
+
+            #[inline]
+            pub fn ceil(&self) -> Self {
+                Self { $($field: self.$field.ceil()),+ }
+            }
+
+            #[inline]
+            pub fn round(&self) -> Self {
+                Self { $($field: self.$field.round()),+ }
+            }
+
+            #[inline]
+            pub fn fract(&self) -> Self {
+                Self { $($field: self.$field.fract()),+ }
+            }
+
+            #[inline]
+            pub fn is_finite(&self) -> bool {
+                true $(&& self.$field.is_finite())+
+            }
+
+            /// Checks every component is within `max_abs_diff` of `rhs`
+            #[inline]
+            pub fn abs_diff_eq(&self, rhs: Self, max_abs_diff: f32) -> bool {
+                true $(&& (self.$field - rhs.$field).abs() <= max_abs_diff)+
+            }
+        }
+    };
+}
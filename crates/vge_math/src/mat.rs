@@ -0,0 +1,479 @@
+use std::ops::Mul;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{Quat, Vec2, Vec3, Vec4};
+
+/// 3x3 column-major matrix, mostly used as a 2D homogeneous transform
+///
+/// Note that WGSL pads every `mat3x3` column to 16 bytes,
+/// upload [`Mat4::from`] instead when using it in a uniform
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+pub struct Mat3 {
+    pub cols: [[f32; 3]; 3],
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat3 {
+    pub const ZERO: Self = Self::from_cols([0.0; 3], [0.0; 3], [0.0; 3]);
+    pub const IDENTITY: Self = Self::from_cols([1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]);
+
+    #[inline]
+    pub const fn from_cols(x: [f32; 3], y: [f32; 3], z: [f32; 3]) -> Self {
+        Self { cols: [x, y, z] }
+    }
+
+    #[inline]
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self::from_cols(
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [translation.x, translation.y, 1.0],
+        )
+    }
+
+    /// Counter-clockwise rotation in radians
+    #[inline]
+    pub fn from_angle(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from_cols([cos, sin, 0.0], [-sin, cos, 0.0], [0.0, 0.0, 1.0])
+    }
+
+    #[inline]
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self::from_cols([scale.x, 0.0, 0.0], [0.0, scale.y, 0.0], [0.0, 0.0, 1.0])
+    }
+
+    /// Scales first, then rotates, then translates
+    #[inline]
+    pub fn from_scale_angle_translation(scale: Vec2, angle: f32, translation: Vec2) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from_cols(
+            [cos * scale.x, sin * scale.x, 0.0],
+            [-sin * scale.y, cos * scale.y, 0.0],
+            [translation.x, translation.y, 1.0],
+        )
+    }
+
+    #[inline]
+    pub fn col(&self, index: usize) -> Vec3 {
+        Vec3::from(self.cols[index])
+    }
+
+    #[inline]
+    pub fn row(&self, index: usize) -> Vec3 {
+        Vec3::new(
+            self.cols[0][index],
+            self.cols[1][index],
+            self.cols[2][index],
+        )
+    }
+
+    #[inline]
+    pub fn transpose(&self) -> Self {
+        let [x, y, z] = [self.row(0), self.row(1), self.row(2)];
+        Self::from_cols([x.x, x.y, x.z], [y.x, y.y, y.z], [z.x, z.y, z.z])
+    }
+
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        let [a, b, c] = self.cols;
+        a[0] * (b[1] * c[2] - c[1] * b[2]) - b[0] * (a[1] * c[2] - c[1] * a[2])
+            + c[0] * (a[1] * b[2] - b[1] * a[2])
+    }
+
+    /// `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let [a, b, c] = self.cols;
+        let inv_det = 1.0 / det;
+        Some(Self::from_cols(
+            [
+                (b[1] * c[2] - c[1] * b[2]) * inv_det,
+                (c[1] * a[2] - a[1] * c[2]) * inv_det,
+                (a[1] * b[2] - b[1] * a[2]) * inv_det,
+            ],
+            [
+                (c[0] * b[2] - b[0] * c[2]) * inv_det,
+                (a[0] * c[2] - c[0] * a[2]) * inv_det,
+                (b[0] * a[2] - a[0] * b[2]) * inv_det,
+            ],
+            [
+                (b[0] * c[1] - c[0] * b[1]) * inv_det,
+                (c[0] * a[1] - a[0] * c[1]) * inv_det,
+                (a[0] * b[1] - b[0] * a[1]) * inv_det,
+            ],
+        ))
+    }
+
+    #[inline]
+    pub fn mul_vec3(&self, rhs: Vec3) -> Vec3 {
+        let [a, b, c] = self.cols;
+        Vec3::new(
+            a[0] * rhs.x + b[0] * rhs.y + c[0] * rhs.z,
+            a[1] * rhs.x + b[1] * rhs.y + c[1] * rhs.z,
+            a[2] * rhs.x + b[2] * rhs.y + c[2] * rhs.z,
+        )
+    }
+
+    /// Applies rotation, scale and translation to a point
+    #[inline]
+    pub fn transform_point2(&self, point: Vec2) -> Vec2 {
+        self.mul_vec3(point.extend(1.0)).xy()
+    }
+
+    /// Applies rotation and scale but not translation
+    #[inline]
+    pub fn transform_vector2(&self, vector: Vec2) -> Vec2 {
+        self.mul_vec3(vector.extend(0.0)).xy()
+    }
+
+    #[inline]
+    pub fn to_cols_array(&self) -> [f32; 9] {
+        bytemuck::cast(self.cols)
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let [x, y, z] = rhs.cols.map(|[x, y, z]| self.mul_vec3(Vec3::new(x, y, z)));
+        Mat3::from_cols([x.x, x.y, x.z], [y.x, y.y, y.z], [z.x, z.y, z.z])
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Self::Output {
+        self.mul_vec3(rhs)
+    }
+}
+
+/// 4x4 column-major matrix, laid out the same as WGSL's `mat4x4<f32>`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+pub struct Mat4 {
+    pub cols: [[f32; 4]; 4],
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Mat4 {
+    pub const ZERO: Self = Self::from_cols([0.0; 4], [0.0; 4], [0.0; 4], [0.0; 4]);
+    pub const IDENTITY: Self = Self::from_cols(
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    );
+
+    #[inline]
+    pub const fn from_cols(x: [f32; 4], y: [f32; 4], z: [f32; 4], w: [f32; 4]) -> Self {
+        Self { cols: [x, y, z, w] }
+    }
+
+    #[inline]
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self::from_cols(
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [translation.x, translation.y, translation.z, 1.0],
+        )
+    }
+
+    #[inline]
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self::from_cols(
+            [scale.x, 0.0, 0.0, 0.0],
+            [0.0, scale.y, 0.0, 0.0],
+            [0.0, 0.0, scale.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        )
+    }
+
+    /// Counter-clockwise rotation around the z axis in radians
+    #[inline]
+    pub fn from_rotation_z(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::from_cols(
+            [cos, sin, 0.0, 0.0],
+            [-sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        )
+    }
+
+    pub fn from_quat(rotation: Quat) -> Self {
+        let Quat { x, y, z, w } = rotation;
+        let (x2, y2, z2) = (x + x, y + y, z + z);
+        let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+        let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+        let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+
+        Self::from_cols(
+            [1.0 - (yy + zz), xy + wz, xz - wy, 0.0],
+            [xy - wz, 1.0 - (xx + zz), yz + wx, 0.0],
+            [xz + wy, yz - wx, 1.0 - (xx + yy), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        )
+    }
+
+    /// Right handed orthographic projection mapping depth to 0..1 like wgpu expects
+    pub fn orthographic_rh(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Self {
+        let rcp_width = 1.0 / (right - left);
+        let rcp_height = 1.0 / (top - bottom);
+        let r = 1.0 / (near - far);
+        Self::from_cols(
+            [rcp_width + rcp_width, 0.0, 0.0, 0.0],
+            [0.0, rcp_height + rcp_height, 0.0, 0.0],
+            [0.0, 0.0, r, 0.0],
+            [
+                -(left + right) * rcp_width,
+                -(top + bottom) * rcp_height,
+                r * near,
+                1.0,
+            ],
+        )
+    }
+
+    #[inline]
+    pub fn transpose(&self) -> Self {
+        let c = self.cols;
+        Self::from_cols(
+            [c[0][0], c[1][0], c[2][0], c[3][0]],
+            [c[0][1], c[1][1], c[2][1], c[3][1]],
+            [c[0][2], c[1][2], c[2][2], c[3][2]],
+            [c[0][3], c[1][3], c[2][3], c[3][3]],
+        )
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let [a, b, c, d] = self.cols;
+        let s0 = a[0] * b[1] - b[0] * a[1];
+        let s1 = a[0] * b[2] - b[0] * a[2];
+        let s2 = a[0] * b[3] - b[0] * a[3];
+        let s3 = a[1] * b[2] - b[1] * a[2];
+        let s4 = a[1] * b[3] - b[1] * a[3];
+        let s5 = a[2] * b[3] - b[2] * a[3];
+        let c5 = c[2] * d[3] - d[2] * c[3];
+        let c4 = c[1] * d[3] - d[1] * c[3];
+        let c3 = c[1] * d[2] - d[1] * c[2];
+        let c2 = c[0] * d[3] - d[0] * c[3];
+        let c1 = c[0] * d[2] - d[0] * c[2];
+        let c0 = c[0] * d[1] - d[0] * c[1];
+        s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0
+    }
+
+    /// `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let [a, b, c, d] = self.cols;
+        let s0 = a[0] * b[1] - b[0] * a[1];
+        let s1 = a[0] * b[2] - b[0] * a[2];
+        let s2 = a[0] * b[3] - b[0] * a[3];
+        let s3 = a[1] * b[2] - b[1] * a[2];
+        let s4 = a[1] * b[3] - b[1] * a[3];
+        let s5 = a[2] * b[3] - b[2] * a[3];
+        let c5 = c[2] * d[3] - d[2] * c[3];
+        let c4 = c[1] * d[3] - d[1] * c[3];
+        let c3 = c[1] * d[2] - d[1] * c[2];
+        let c2 = c[0] * d[3] - d[0] * c[3];
+        let c1 = c[0] * d[2] - d[0] * c[2];
+        let c0 = c[0] * d[1] - d[0] * c[1];
+
+        let det = s0 * c5 - s1 * c4 + s2 * c3 + s3 * c2 - s4 * c1 + s5 * c0;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        Some(Self::from_cols(
+            [
+                (b[1] * c5 - b[2] * c4 + b[3] * c3) * inv_det,
+                (-a[1] * c5 + a[2] * c4 - a[3] * c3) * inv_det,
+                (d[1] * s5 - d[2] * s4 + d[3] * s3) * inv_det,
+                (-c[1] * s5 + c[2] * s4 - c[3] * s3) * inv_det,
+            ],
+            [
+                (-b[0] * c5 + b[2] * c2 - b[3] * c1) * inv_det,
+                (a[0] * c5 - a[2] * c2 + a[3] * c1) * inv_det,
+                (-d[0] * s5 + d[2] * s2 - d[3] * s1) * inv_det,
+                (c[0] * s5 - c[2] * s2 + c[3] * s1) * inv_det,
+            ],
+            [
+                (b[0] * c4 - b[1] * c2 + b[3] * c0) * inv_det,
+                (-a[0] * c4 + a[1] * c2 - a[3] * c0) * inv_det,
+                (d[0] * s4 - d[1] * s2 + d[3] * s0) * inv_det,
+                (-c[0] * s4 + c[1] * s2 - c[3] * s0) * inv_det,
+            ],
+            [
+                (-b[0] * c3 + b[1] * c1 - b[2] * c0) * inv_det,
+                (a[0] * c3 - a[1] * c1 + a[2] * c0) * inv_det,
+                (-d[0] * s3 + d[1] * s1 - d[2] * s0) * inv_det,
+                (c[0] * s3 - c[1] * s1 + c[2] * s0) * inv_det,
+            ],
+        ))
+    }
+
+    #[inline]
+    pub fn col(&self, index: usize) -> Vec4 {
+        Vec4::from(self.cols[index])
+    }
+
+    #[inline]
+    pub fn row(&self, index: usize) -> Vec4 {
+        Vec4::from(self.cols.map(|col| col[index]))
+    }
+
+    #[inline]
+    pub fn mul_vec4(&self, rhs: Vec4) -> Vec4 {
+        Vec4::from(self.mul_array(rhs.to_array()))
+    }
+
+    #[inline]
+    fn mul_array(&self, rhs: [f32; 4]) -> [f32; 4] {
+        let [a, b, c, d] = self.cols;
+        std::array::from_fn(|i| a[i] * rhs[0] + b[i] * rhs[1] + c[i] * rhs[2] + d[i] * rhs[3])
+    }
+
+    /// Applies the full transform to a point, including the perspective divide
+    #[inline]
+    pub fn transform_point3(&self, point: Vec3) -> Vec3 {
+        let v = self.mul_vec4(point.extend(1.0));
+        v.xyz() / v.w
+    }
+
+    /// Applies rotation and scale but not translation
+    #[inline]
+    pub fn transform_vector3(&self, vector: Vec3) -> Vec3 {
+        self.mul_vec4(vector.extend(0.0)).xyz()
+    }
+
+    #[inline]
+    pub fn to_cols_array(&self) -> [f32; 16] {
+        bytemuck::cast(self.cols)
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let [x, y, z, w] = rhs.cols.map(|col| self.mul_array(col));
+        Mat4::from_cols(x, y, z, w)
+    }
+}
+
+impl Mul<Vec4> for Mat4 {
+    type Output = Vec4;
+
+    fn mul(self, rhs: Vec4) -> Self::Output {
+        self.mul_vec4(rhs)
+    }
+}
+
+impl From<Mat3> for Mat4 {
+    /// Embeds a 2D homogeneous transform, z is left untouched
+    fn from(value: Mat3) -> Self {
+        let [x, y, w] = value.cols;
+        Self::from_cols(
+            [x[0], x[1], 0.0, x[2]],
+            [y[0], y[1], 0.0, y[2]],
+            [0.0, 0.0, 1.0, 0.0],
+            [w[0], w[1], 0.0, w[2]],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+
+    use super::*;
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-4, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn mat3_inverse_undoes_transform() {
+        let m = Mat3::from_scale_angle_translation(Vec2::new(2.0, 0.5), 0.7, Vec2::new(3.0, -4.0));
+        let inverse = m.inverse().unwrap();
+        assert_close(
+            &(m * inverse).to_cols_array(),
+            &Mat3::IDENTITY.to_cols_array(),
+        );
+
+        let point = Vec2::new(1.5, -2.0);
+        let back = inverse.transform_point2(m.transform_point2(point));
+        assert_close(&[back.x, back.y], &[point.x, point.y]);
+    }
+
+    #[test]
+    fn mat4_inverse_undoes_transform() {
+        let m = Mat4::from_translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::from_quat(Quat::from_axis_angle(
+                Vec3::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.0),
+                0.9,
+            ))
+            * Mat4::from_scale(Vec3::new(2.0, 3.0, 0.5));
+        let inverse = m.inverse().unwrap();
+        assert_close(
+            &(m * inverse).to_cols_array(),
+            &Mat4::IDENTITY.to_cols_array(),
+        );
+        assert_close(
+            &(inverse * m).to_cols_array(),
+            &Mat4::IDENTITY.to_cols_array(),
+        );
+    }
+
+    #[test]
+    fn mat4_inverse_of_orthographic() {
+        let m = Mat4::orthographic_rh(-320.0, 320.0, -180.0, 180.0, -1.0, 1.0);
+        let inverse = m.inverse().unwrap();
+        let corner = inverse.transform_point3(Vec3::new(1.0, -1.0, 0.0));
+        assert_close(&[corner.x, corner.y], &[320.0, -180.0]);
+    }
+
+    #[test]
+    fn singular_has_no_inverse() {
+        assert!(Mat3::ZERO.inverse().is_none());
+        assert!(Mat4::ZERO.inverse().is_none());
+        assert!(
+            Mat4::from_scale(Vec3::new(1.0, 0.0, 1.0))
+                .inverse()
+                .is_none()
+        );
+        assert!(
+            Mat3::from_scale(Vec2::new(f32::NAN, 1.0))
+                .inverse()
+                .is_none()
+        );
+    }
+}
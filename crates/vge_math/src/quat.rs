@@ -0,0 +1,160 @@
+use std::ops::Mul;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{Mat4, Vec3};
+
+/// Rotation quaternion, expected to be normalized
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Quat {
+    pub const IDENTITY: Self = Self::from_xyzw(0.0, 0.0, 0.0, 1.0);
+
+    #[inline]
+    pub const fn from_xyzw(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    /// Counter-clockwise rotation of `angle` radians around a normalized `axis`
+    #[inline]
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        Self::from_xyzw(axis.x * sin, axis.y * sin, axis.z * sin, cos)
+    }
+
+    /// Counter-clockwise rotation around the z axis in radians, the only one 2D cares about
+    #[inline]
+    pub fn from_rotation_z(angle: f32) -> Self {
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        Self::from_xyzw(0.0, 0.0, sin, cos)
+    }
+
+    /// Angle around the z axis, ignores any rotation around the other axes
+    #[inline]
+    pub fn to_rotation_z(&self) -> f32 {
+        (2.0 * (self.w * self.z + self.x * self.y))
+            .atan2(1.0 - 2.0 * (self.y * self.y + self.z * self.z))
+    }
+
+    #[inline]
+    pub fn dot(&self, rhs: Quat) -> f32 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z + self.w * rhs.w
+    }
+
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.dot(*self).sqrt()
+    }
+
+    #[inline]
+    pub fn normalize(&self) -> Self {
+        let rcp_len = 1.0 / self.length();
+        Self::from_xyzw(
+            self.x * rcp_len,
+            self.y * rcp_len,
+            self.z * rcp_len,
+            self.w * rcp_len,
+        )
+    }
+
+    #[inline]
+    pub fn conjugate(&self) -> Self {
+        Self::from_xyzw(-self.x, -self.y, -self.z, self.w)
+    }
+
+    /// Same as [`Quat::conjugate`] for normalized quaternions
+    #[inline]
+    pub fn inverse(&self) -> Self {
+        self.conjugate()
+    }
+
+    #[inline]
+    pub fn mul_quat(&self, rhs: Quat) -> Self {
+        let Quat { x, y, z, w } = *self;
+        Self::from_xyzw(
+            w * rhs.x + x * rhs.w + y * rhs.z - z * rhs.y,
+            w * rhs.y - x * rhs.z + y * rhs.w + z * rhs.x,
+            w * rhs.z + x * rhs.y - y * rhs.x + z * rhs.w,
+            w * rhs.w - x * rhs.x - y * rhs.y - z * rhs.z,
+        )
+    }
+
+    #[inline]
+    pub fn mul_vec3(&self, rhs: Vec3) -> Vec3 {
+        let Quat { x, y, z, w } = *self;
+        // t = 2 * cross(q.xyz, v)
+        let tx = 2.0 * (y * rhs.z - z * rhs.y);
+        let ty = 2.0 * (z * rhs.x - x * rhs.z);
+        let tz = 2.0 * (x * rhs.y - y * rhs.x);
+        // v + w * t + cross(q.xyz, t)
+        Vec3::new(
+            rhs.x + w * tx + (y * tz - z * ty),
+            rhs.y + w * ty + (z * tx - x * tz),
+            rhs.z + w * tz + (x * ty - y * tx),
+        )
+    }
+
+    /// Spherical interpolation along the shortest path, `t` of 0 returns `self`
+    pub fn slerp(&self, end: Quat, t: f32) -> Self {
+        let mut end = end;
+        let mut dot = self.dot(end);
+        if dot < 0.0 {
+            end = Self::from_xyzw(-end.x, -end.y, -end.z, -end.w);
+            dot = -dot;
+        }
+
+        let (a, b) = if dot > 0.9995 {
+            // nearly parallel, a plain lerp is accurate enough and avoids dividing by ~0
+            (1.0 - t, t)
+        } else {
+            let theta = dot.acos();
+            let rcp_sin = 1.0 / theta.sin();
+            (
+                ((1.0 - t) * theta).sin() * rcp_sin,
+                (t * theta).sin() * rcp_sin,
+            )
+        };
+
+        Self::from_xyzw(
+            self.x * a + end.x * b,
+            self.y * a + end.y * b,
+            self.z * a + end.z * b,
+            self.w * a + end.w * b,
+        )
+        .normalize()
+    }
+
+    #[inline]
+    pub fn to_mat4(&self) -> Mat4 {
+        Mat4::from_quat(*self)
+    }
+}
+
+impl Mul for Quat {
+    type Output = Quat;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.mul_quat(rhs)
+    }
+}
+
+impl Mul<Vec3> for Quat {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Self::Output {
+        self.mul_vec3(rhs)
+    }
+}
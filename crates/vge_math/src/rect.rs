@@ -0,0 +1,141 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::Vec2;
+
+/// Axis aligned rectangle, `min` is expected to be smaller than `max` on both axes
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    #[inline]
+    pub const fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Rectangle spanning two opposite corners given in any order
+    #[inline]
+    pub fn from_corners(a: Vec2, b: Vec2) -> Self {
+        Self::new(a.min(b), a.max(b))
+    }
+
+    #[inline]
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        let half = size * 0.5;
+        Self::new(center - half, center + half)
+    }
+
+    #[inline]
+    pub fn from_center_half_size(center: Vec2, half_size: Vec2) -> Self {
+        Self::new(center - half_size, center + half_size)
+    }
+
+    #[inline]
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) * 0.5
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    #[inline]
+    pub fn half_size(&self) -> Vec2 {
+        self.size() * 0.5
+    }
+
+    #[inline]
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    #[inline]
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    #[inline]
+    pub fn area(&self) -> f32 {
+        self.width() * self.height()
+    }
+
+    /// True if the rectangle has no area
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.max.x <= self.min.x || self.max.y <= self.min.y
+    }
+
+    /// Corners counter-clockwise with y up, starting at `min`
+    #[inline]
+    pub fn corners(&self) -> [Vec2; 4] {
+        [
+            self.min,
+            Vec2::new(self.max.x, self.min.y),
+            self.max,
+            Vec2::new(self.min.x, self.max.y),
+        ]
+    }
+
+    /// Points on the edges count as inside
+    #[inline]
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.x >= self.min.x
+            && point.x <= self.max.x
+            && point.y >= self.min.y
+            && point.y <= self.max.y
+    }
+
+    #[inline]
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    /// Rectangles only touching at an edge do not intersect
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min.x < other.max.x
+            && self.max.x > other.min.x
+            && self.min.y < other.max.y
+            && self.max.y > other.min.y
+    }
+
+    /// Overlapping area of both rectangles, `None` if they do not intersect
+    #[inline]
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let rect = Self::new(self.min.max(other.min), self.max.min(other.max));
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    /// Smallest rectangle containing both
+    #[inline]
+    pub fn union(&self, other: &Rect) -> Rect {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    /// Smallest rectangle containing both `self` and `point`
+    #[inline]
+    pub fn union_point(&self, point: Vec2) -> Rect {
+        Self::new(self.min.min(point), self.max.max(point))
+    }
+
+    /// Grows every side by `amount`, negative values shrink it
+    #[inline]
+    pub fn expand(&self, amount: f32) -> Rect {
+        Self::new(self.min - amount, self.max + amount)
+    }
+
+    #[inline]
+    pub fn translate(&self, offset: Vec2) -> Rect {
+        Self::new(self.min + offset, self.max + offset)
+    }
+
+    /// Point inside or on the rectangle closest to `point`
+    #[inline]
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        point.clamp(self.min, self.max)
+    }
+}
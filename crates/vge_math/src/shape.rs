@@ -0,0 +1,384 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::{
+    Rect, Transform2D, Vec2,
+    collision::{self, Manifold, RayHit},
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    #[inline]
+    pub const fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Points on the edge count as inside
+    #[inline]
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    /// Circles only touching at a point do not intersect
+    #[inline]
+    pub fn intersects(&self, other: &Circle) -> bool {
+        let radius = self.radius + other.radius;
+        self.center.distance_squared(other.center) < radius * radius
+    }
+
+    #[inline]
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        self.center
+            .distance_squared(rect.closest_point(self.center))
+            < self.radius * self.radius
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> Rect {
+        Rect::from_center_half_size(self.center, Vec2::splat(self.radius))
+    }
+}
+
+/// Line segment between two points
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct Segment {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl Segment {
+    #[inline]
+    pub const fn new(start: Vec2, end: Vec2) -> Self {
+        Self { start, end }
+    }
+
+    /// Not normalized, goes from `start` to `end`
+    #[inline]
+    pub fn direction(&self) -> Vec2 {
+        self.end - self.start
+    }
+
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.direction().length()
+    }
+
+    #[inline]
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        collision::closest_point_on_segment(self.start, self.end, point)
+    }
+
+    #[inline]
+    pub fn distance_to_point(&self, point: Vec2) -> f32 {
+        self.closest_point(point).distance(point)
+    }
+
+    /// Point where both segments cross, `None` for parallel segments
+    pub fn intersection(&self, other: &Segment) -> Option<Vec2> {
+        let d = self.direction();
+        let e = other.direction();
+        let denom = d.perp_dot(e);
+        if denom.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let offset = other.start - self.start;
+        let t = offset.perp_dot(e) / denom;
+        let u = offset.perp_dot(d) / denom;
+        ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| self.start + d * t)
+    }
+
+    #[inline]
+    pub fn bounds(&self) -> Rect {
+        Rect::from_corners(self.start, self.end)
+    }
+}
+
+/// Half-line starting at `origin`, `direction` is kept normalized
+/// so the time of a hit is also its distance from the origin
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct Ray {
+    pub origin: Vec2,
+    pub direction: Vec2,
+}
+
+impl Ray {
+    #[inline]
+    pub fn new(origin: Vec2, direction: Vec2) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+        }
+    }
+
+    /// Ray from `start` towards `end`, hits past `end` still count
+    #[inline]
+    pub fn from_points(start: Vec2, end: Vec2) -> Self {
+        Self::new(start, end - start)
+    }
+
+    #[inline]
+    pub fn at(&self, time: f32) -> Vec2 {
+        self.origin + self.direction * time
+    }
+
+    /// Rays starting inside a shape hit it at time 0, facing back along the ray
+    pub fn cast(&self, shape: &Shape) -> Option<RayHit> {
+        match shape {
+            Shape::Circle(circle) => self.cast_circle(circle),
+            Shape::Rect(rect) => self.cast_rect(rect),
+            Shape::Polygon(polygon) => self.cast_polygon(polygon),
+        }
+    }
+
+    pub fn cast_rect(&self, rect: &Rect) -> Option<RayHit> {
+        collision::ray_convex(self, &rect.corners())
+    }
+
+    pub fn cast_circle(&self, circle: &Circle) -> Option<RayHit> {
+        let offset = self.origin - circle.center;
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - circle.radius * circle.radius;
+        if c <= 0.0 {
+            return Some(collision::inside_hit(self));
+        }
+        if b > 0.0 {
+            return None;
+        }
+
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let time = -b - discriminant.sqrt();
+        let point = self.at(time);
+        Some(RayHit {
+            point,
+            normal: (point - circle.center).normalize_or(-self.direction),
+            time,
+        })
+    }
+
+    /// The normal faces the side of the segment the ray came from
+    pub fn cast_segment(&self, segment: &Segment) -> Option<RayHit> {
+        let e = segment.direction();
+        let denom = self.direction.perp_dot(e);
+        if denom.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let offset = segment.start - self.origin;
+        let time = offset.perp_dot(e) / denom;
+        let u = offset.perp_dot(self.direction) / denom;
+        if time < 0.0 || !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let normal = e.perp().normalize();
+        Some(RayHit {
+            point: self.at(time),
+            normal: if normal.dot(self.direction) > 0.0 {
+                -normal
+            } else {
+                normal
+            },
+            time,
+        })
+    }
+
+    pub fn cast_polygon(&self, polygon: &Polygon) -> Option<RayHit> {
+        collision::ray_convex(self, polygon.points())
+    }
+}
+
+/// Convex polygon with its points wound counter-clockwise
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Polygon {
+    points: Vec<Vec2>,
+}
+
+impl Polygon {
+    /// `points` have to form a convex polygon, clockwise winding is flipped
+    pub fn new(points: impl Into<Vec<Vec2>>) -> Self {
+        let mut polygon = Self {
+            points: points.into(),
+        };
+        if polygon.signed_area() < 0.0 {
+            polygon.points.reverse();
+        }
+        polygon
+    }
+
+    pub fn from_rect(rect: &Rect) -> Self {
+        Self {
+            points: rect.corners().to_vec(),
+        }
+    }
+
+    /// Polygon with `sides` corners on a circle, the first one pointing right
+    pub fn regular(center: Vec2, radius: f32, sides: usize) -> Self {
+        let step = std::f32::consts::TAU / sides as f32;
+        Self {
+            points: (0..sides)
+                .map(|i| center + Vec2::from_angle(step * i as f32) * radius)
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// Edges as segments going counter-clockwise
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let next = self.points.iter().cycle().skip(1);
+        self.points
+            .iter()
+            .zip(next)
+            .map(|(start, end)| Segment::new(*start, *end))
+    }
+
+    /// Positive for counter-clockwise winding
+    fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|edge| edge.start.perp_dot(edge.end))
+            .sum::<f32>()
+            * 0.5
+    }
+
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    /// Center of mass, falls back to the average of the points for degenerate polygons
+    pub fn centroid(&self) -> Vec2 {
+        let area = self.signed_area();
+        if area.abs() <= f32::EPSILON {
+            return self.points.iter().copied().sum::<Vec2>() / self.points.len().max(1) as f32;
+        }
+
+        let sum: Vec2 = self
+            .edges()
+            .map(|edge| (edge.start + edge.end) * edge.start.perp_dot(edge.end))
+            .sum();
+        sum / (6.0 * area)
+    }
+
+    /// Points on the edges count as inside
+    pub fn contains(&self, point: Vec2) -> bool {
+        !self.points.is_empty()
+            && self
+                .edges()
+                .all(|edge| edge.direction().perp_dot(point - edge.start) >= 0.0)
+    }
+
+    pub fn bounds(&self) -> Rect {
+        let first = self.points.first().copied().unwrap_or_default();
+        self.points
+            .iter()
+            .fold(Rect::new(first, first), |rect, point| {
+                rect.union_point(*point)
+            })
+    }
+
+    /// Copy of the polygon with every point run through `transform`
+    pub fn transformed(&self, transform: &Transform2D) -> Self {
+        Self::new(
+            self.points
+                .iter()
+                .map(|point| transform.transform_point(*point))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// Any of the shapes collision works with
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    Circle(Circle),
+    Rect(Rect),
+    Polygon(Polygon),
+}
+
+impl Shape {
+    pub fn bounds(&self) -> Rect {
+        match self {
+            Shape::Circle(circle) => circle.bounds(),
+            Shape::Rect(rect) => *rect,
+            Shape::Polygon(polygon) => polygon.bounds(),
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        match self {
+            Shape::Circle(circle) => circle.contains(point),
+            Shape::Rect(rect) => rect.contains(point),
+            Shape::Polygon(polygon) => polygon.contains(point),
+        }
+    }
+
+    /// Shapes only touching do not intersect
+    pub fn intersects(&self, other: &Shape) -> bool {
+        match (self, other) {
+            (Shape::Circle(a), Shape::Circle(b)) => a.intersects(b),
+            (Shape::Rect(a), Shape::Rect(b)) => a.intersects(b),
+            (Shape::Circle(circle), Shape::Rect(rect))
+            | (Shape::Rect(rect), Shape::Circle(circle)) => circle.intersects_rect(rect),
+            _ => self.manifold(other).is_some(),
+        }
+    }
+
+    /// How to push `other` out of `self`, see [`Manifold`]
+    pub fn manifold(&self, other: &Shape) -> Option<Manifold> {
+        match (self, other) {
+            (Shape::Circle(a), Shape::Circle(b)) => collision::circle_circle(a, b),
+            (Shape::Circle(a), Shape::Rect(b)) => {
+                collision::convex_circle(&b.corners(), a).map(Manifold::flip)
+            }
+            (Shape::Circle(a), Shape::Polygon(b)) => {
+                collision::convex_circle(b.points(), a).map(Manifold::flip)
+            }
+            (Shape::Rect(a), Shape::Circle(b)) => collision::convex_circle(&a.corners(), b),
+            (Shape::Polygon(a), Shape::Circle(b)) => collision::convex_circle(a.points(), b),
+            (Shape::Rect(a), Shape::Rect(b)) => {
+                collision::convex_convex(&a.corners(), &b.corners())
+            }
+            (Shape::Rect(a), Shape::Polygon(b)) => {
+                collision::convex_convex(&a.corners(), b.points())
+            }
+            (Shape::Polygon(a), Shape::Rect(b)) => {
+                collision::convex_convex(a.points(), &b.corners())
+            }
+            (Shape::Polygon(a), Shape::Polygon(b)) => {
+                collision::convex_convex(a.points(), b.points())
+            }
+        }
+    }
+}
+
+impl From<Circle> for Shape {
+    fn from(value: Circle) -> Self {
+        Shape::Circle(value)
+    }
+}
+
+impl From<Rect> for Shape {
+    fn from(value: Rect) -> Self {
+        Shape::Rect(value)
+    }
+}
+
+impl From<Polygon> for Shape {
+    fn from(value: Polygon) -> Self {
+        Shape::Polygon(value)
+    }
+}
@@ -0,0 +1,282 @@
+use std::ops::Mul;
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{Mat3, Mat4, Vec2};
+
+/// 2D affine transform, a 2x2 matrix followed by a translation
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+pub struct Affine2 {
+    pub x_axis: Vec2,
+    pub y_axis: Vec2,
+    pub translation: Vec2,
+}
+
+impl Default for Affine2 {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine2 {
+    pub const IDENTITY: Self = Self {
+        x_axis: Vec2::X,
+        y_axis: Vec2::Y,
+        translation: Vec2::ZERO,
+    };
+
+    #[inline]
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Counter-clockwise rotation in radians
+    #[inline]
+    pub fn from_angle(angle: f32) -> Self {
+        Self::from_scale_angle_translation(Vec2::ONE, angle, Vec2::ZERO)
+    }
+
+    #[inline]
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self {
+            x_axis: Vec2::new(scale.x, 0.0),
+            y_axis: Vec2::new(0.0, scale.y),
+            translation: Vec2::ZERO,
+        }
+    }
+
+    /// Scales first, then rotates, then translates
+    #[inline]
+    pub fn from_scale_angle_translation(scale: Vec2, angle: f32, translation: Vec2) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self {
+            x_axis: Vec2::new(cos * scale.x, sin * scale.x),
+            y_axis: Vec2::new(-sin * scale.y, cos * scale.y),
+            translation,
+        }
+    }
+
+    #[inline]
+    pub fn transform_point2(&self, point: Vec2) -> Vec2 {
+        self.transform_vector2(point) + self.translation
+    }
+
+    /// Applies rotation and scale but not translation
+    #[inline]
+    pub fn transform_vector2(&self, vector: Vec2) -> Vec2 {
+        Vec2::new(
+            self.x_axis.x * vector.x + self.y_axis.x * vector.y,
+            self.x_axis.y * vector.x + self.y_axis.y * vector.y,
+        )
+    }
+
+    #[inline]
+    pub fn determinant(&self) -> f32 {
+        self.x_axis.x * self.y_axis.y - self.y_axis.x * self.x_axis.y
+    }
+
+    /// `None` if the transform collapses everything onto a line or a point
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+
+        let rcp_det = 1.0 / det;
+        let x_axis = Vec2::new(self.y_axis.y * rcp_det, -self.x_axis.y * rcp_det);
+        let y_axis = Vec2::new(-self.y_axis.x * rcp_det, self.x_axis.x * rcp_det);
+        let mut inverse = Self {
+            x_axis,
+            y_axis,
+            translation: Vec2::ZERO,
+        };
+        inverse.translation = -inverse.transform_vector2(self.translation);
+        Some(inverse)
+    }
+
+    #[inline]
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::from(*self)
+    }
+}
+
+impl Mul for Affine2 {
+    type Output = Affine2;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Affine2 {
+            x_axis: self.transform_vector2(rhs.x_axis),
+            y_axis: self.transform_vector2(rhs.y_axis),
+            translation: self.transform_point2(rhs.translation),
+        }
+    }
+}
+
+impl From<Affine2> for Mat3 {
+    fn from(value: Affine2) -> Self {
+        Mat3::from_cols(
+            [value.x_axis.x, value.x_axis.y, 0.0],
+            [value.y_axis.x, value.y_axis.y, 0.0],
+            [value.translation.x, value.translation.y, 1.0],
+        )
+    }
+}
+
+impl From<Affine2> for Mat4 {
+    fn from(value: Affine2) -> Self {
+        Mat4::from(Mat3::from(value))
+    }
+}
+
+/// Translation, rotation and scale of something in 2D,
+/// applied as scale first, then rotation, then translation
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Zeroable, Pod)]
+pub struct Transform2D {
+    pub translation: Vec2,
+    /// Counter-clockwise, in radians
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform2D {
+    pub const IDENTITY: Self = Self {
+        translation: Vec2::ZERO,
+        rotation: 0.0,
+        scale: Vec2::ONE,
+    };
+
+    #[inline]
+    pub fn new(translation: Vec2, rotation: f32, scale: Vec2) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    #[inline]
+    pub fn from_translation(translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub fn from_rotation(rotation: f32) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub fn from_scale(scale: Vec2) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[inline]
+    pub fn with_translation(mut self, translation: Vec2) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    #[inline]
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    #[inline]
+    pub fn with_scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    #[inline]
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.transform_vector(point) + self.translation
+    }
+
+    /// Applies rotation and scale but not translation
+    #[inline]
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let Vec2 { x, y } = vector * self.scale;
+        Vec2::new(x * cos - y * sin, x * sin + y * cos)
+    }
+
+    /// Maps a point from the space this transform outputs back to its local space
+    #[inline]
+    pub fn inverse_transform_point(&self, point: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let Vec2 { x, y } = point - self.translation;
+        Vec2::new(x * cos + y * sin, -x * sin + y * cos) / self.scale
+    }
+
+    /// Applies `child` first, then `self`, like parenting `child` to `self`
+    ///
+    /// Exact as long as `self` has a uniform scale or `child` has no rotation,
+    /// any other combination skews and should go through [`Affine2`] instead
+    pub fn mul_transform(&self, child: Transform2D) -> Self {
+        Self {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation + child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+
+    /// Undoes this transform, exact under the same conditions as [`Transform2D::mul_transform`]
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Self {
+            translation: Vec2::ZERO,
+            rotation: -self.rotation,
+            scale: 1.0 / self.scale,
+        };
+        inverse.translation = -inverse.transform_vector(self.translation);
+        inverse
+    }
+
+    #[inline]
+    pub fn to_affine(&self) -> Affine2 {
+        Affine2::from_scale_angle_translation(self.scale, self.rotation, self.translation)
+    }
+
+    #[inline]
+    pub fn to_mat3(&self) -> Mat3 {
+        Mat3::from_scale_angle_translation(self.scale, self.rotation, self.translation)
+    }
+
+    #[inline]
+    pub fn to_mat4(&self) -> Mat4 {
+        Mat4::from(self.to_mat3())
+    }
+}
+
+impl Mul for Transform2D {
+    type Output = Transform2D;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.mul_transform(rhs)
+    }
+}
+
+impl From<Transform2D> for Affine2 {
+    fn from(value: Transform2D) -> Self {
+        value.to_affine()
+    }
+}
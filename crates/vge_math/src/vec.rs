@@ -0,0 +1,317 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::{IVec2, UVec2};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl_vec!(Vec2, f32, 2, { x, y });
+impl_vec_signed!(Vec2, { x, y });
+impl_vec_float!(Vec2, { x, y });
+
+impl Vec2 {
+    pub const X: Self = Self::new(1.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0);
+
+    #[inline]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Unit vector pointing `angle` radians counter-clockwise from the x axis
+    #[inline]
+    pub fn from_angle(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin)
+    }
+
+    /// Angle from the x axis in radians, within -π..=π
+    #[inline]
+    pub fn to_angle(&self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// Signed angle in radians to rotate `self` onto `rhs`, counter-clockwise is positive
+    #[inline]
+    pub fn angle_to(&self, rhs: Self) -> f32 {
+        self.perp_dot(rhs).atan2(self.dot(rhs))
+    }
+
+    /// Rotated 90 degrees counter-clockwise
+    #[inline]
+    pub fn perp(&self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// The z of the 3D cross product, positive when `rhs` is counter-clockwise of `self`
+    #[inline]
+    pub fn perp_dot(&self, rhs: Self) -> f32 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    /// Rotates counter-clockwise by `angle` radians
+    #[inline]
+    pub fn rotate(&self, angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    #[inline]
+    pub fn yx(&self) -> Self {
+        Self::new(self.y, self.x)
+    }
+
+    #[inline]
+    pub fn extend(&self, z: f32) -> Vec3 {
+        Vec3::new(self.x, self.y, z)
+    }
+
+    /// Truncates towards zero
+    #[inline]
+    pub fn as_ivec2(&self) -> IVec2 {
+        IVec2::new(self.x as i32, self.y as i32)
+    }
+
+    /// Truncates towards zero, negative components saturate to 0
+    #[inline]
+    pub fn as_uvec2(&self) -> UVec2 {
+        UVec2::new(self.x as u32, self.y as u32)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl_vec!(Vec3, f32, 3, { x, y, z });
+impl_vec_signed!(Vec3, { x, y, z });
+impl_vec_float!(Vec3, { x, y, z });
+
+impl Vec3 {
+    pub const X: Self = Self::new(1.0, 0.0, 0.0);
+    pub const Y: Self = Self::new(0.0, 1.0, 0.0);
+    pub const Z: Self = Self::new(0.0, 0.0, 1.0);
+
+    #[inline]
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    #[inline]
+    pub fn cross(&self, rhs: Self) -> Self {
+        Self::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+
+    /// Unsigned angle between the two vectors in radians
+    #[inline]
+    pub fn angle_between(&self, rhs: Self) -> f32 {
+        self.cross(rhs).length().atan2(self.dot(rhs))
+    }
+
+    #[inline]
+    pub fn xy(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    #[inline]
+    pub fn xz(&self) -> Vec2 {
+        Vec2::new(self.x, self.z)
+    }
+
+    #[inline]
+    pub fn yz(&self) -> Vec2 {
+        Vec2::new(self.y, self.z)
+    }
+
+    #[inline]
+    pub fn zyx(&self) -> Self {
+        Self::new(self.z, self.y, self.x)
+    }
+
+    /// Same as [`Vec3::xy`], drops z
+    #[inline]
+    pub fn truncate(&self) -> Vec2 {
+        self.xy()
+    }
+
+    #[inline]
+    pub fn extend(&self, w: f32) -> Vec4 {
+        Vec4::new(self.x, self.y, self.z, w)
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+pub struct Vec4 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl_vec!(Vec4, f32, 4, { x, y, z, w });
+impl_vec_signed!(Vec4, { x, y, z, w });
+impl_vec_float!(Vec4, { x, y, z, w });
+
+impl Vec4 {
+    #[inline]
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    #[inline]
+    pub fn xy(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    #[inline]
+    pub fn xyz(&self) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z)
+    }
+
+    /// Same as [`Vec4::xyz`], drops w
+    #[inline]
+    pub fn truncate(&self) -> Vec3 {
+        self.xyz()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use super::*;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.abs_diff_eq(b, 1e-5), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn operators() {
+        let a = Vec2::new(6.0, -4.0);
+        let b = Vec2::new(2.0, 3.0);
+        assert_eq!(a + b, Vec2::new(8.0, -1.0));
+        assert_eq!(a - b, Vec2::new(4.0, -7.0));
+        assert_eq!(a * b, Vec2::new(12.0, -12.0));
+        assert_eq!(a / b, Vec2::new(3.0, -4.0 / 3.0));
+        assert_eq!(a % b, Vec2::new(0.0, -1.0));
+        assert_eq!(a * 0.5, Vec2::new(3.0, -2.0));
+        assert_eq!(12.0 / b, Vec2::new(6.0, 4.0));
+        assert_eq!(-a, Vec2::new(-6.0, 4.0));
+
+        let mut c = a;
+        c += b;
+        c *= 2.0;
+        c -= Vec2::ONE;
+        assert_eq!(c, Vec2::new(15.0, -3.0));
+
+        assert_eq!(
+            [a, b, Vec2::ONE].into_iter().sum::<Vec2>(),
+            Vec2::new(9.0, 0.0)
+        );
+        assert_eq!(
+            Vec3::new(1.0, 2.0, 3.0) * Vec3::splat(2.0),
+            Vec3::new(2.0, 4.0, 6.0)
+        );
+        assert_eq!(
+            Vec4::ONE - Vec4::new(1.0, 2.0, 3.0, 4.0),
+            Vec4::new(0.0, -1.0, -2.0, -3.0)
+        );
+    }
+
+    #[test]
+    fn helpers() {
+        let v = Vec2::new(-3.0, 4.0);
+        assert_eq!(v.length(), 5.0);
+        assert_eq!(v.abs(), Vec2::new(3.0, 4.0));
+        assert_eq!(Vec2::new(-2.0, 0.0).signum(), Vec2::new(-1.0, 0.0));
+        assert_eq!(v.min_element(), -3.0);
+        assert_eq!(v.max_element(), 4.0);
+        assert_eq!(v.clamp(Vec2::ZERO, Vec2::splat(2.0)), Vec2::new(0.0, 2.0));
+        assert_eq!(v.normalize(), Vec2::new(-0.6, 0.8));
+        assert_eq!(Vec2::ZERO.normalize_or_zero(), Vec2::ZERO);
+        assert_eq!(v.clamp_length_max(2.5), Vec2::new(-1.5, 2.0));
+        assert_eq!(Vec2::ZERO.lerp(v, 0.5), Vec2::new(-1.5, 2.0));
+        assert_eq!(Vec2::new(1.5, -1.5).floor(), Vec2::new(1.0, -2.0));
+    }
+
+    #[test]
+    fn angles() {
+        assert_close(Vec2::from_angle(FRAC_PI_2), Vec2::Y);
+        assert_eq!(Vec2::new(-1.0, 0.0).to_angle(), PI);
+        assert!((Vec2::X.angle_to(Vec2::Y) - FRAC_PI_2).abs() < 1e-6);
+        assert!((Vec2::Y.angle_to(Vec2::X) + FRAC_PI_2).abs() < 1e-6);
+        assert!((Vec2::new(1.0, 1.0).angle_to(Vec2::new(-1.0, -1.0)).abs() - PI).abs() < 1e-6);
+
+        assert_close(Vec2::X.rotate(FRAC_PI_2), Vec2::Y);
+        assert_close(
+            Vec2::new(2.0, 0.0).rotate(-FRAC_PI_4),
+            Vec2::new(2.0, -2.0) / 2f32.sqrt(),
+        );
+
+        assert!((Vec3::X.angle_between(Vec3::Z) - FRAC_PI_2).abs() < 1e-6);
+        assert!((Vec3::Z.angle_between(-Vec3::Z) - PI).abs() < 1e-6);
+    }
+
+    #[test]
+    fn perp_and_cross() {
+        let v = Vec2::new(3.0, 1.0);
+        assert_eq!(v.perp(), Vec2::new(-1.0, 3.0));
+        assert_eq!(v.dot(v.perp()), 0.0);
+        assert!(v.perp_dot(v.perp()) > 0.0);
+        assert!(v.perp_dot(-v.perp()) < 0.0);
+        assert_eq!(Vec3::X.cross(Vec3::Y), Vec3::Z);
+        assert_eq!(Vec3::Y.cross(Vec3::X), -Vec3::Z);
+    }
+
+    #[test]
+    fn reflect_and_project() {
+        let v = Vec2::new(2.0, -3.0);
+        assert_eq!(v.reflect(Vec2::Y), Vec2::new(2.0, 3.0));
+        assert_eq!(v.project(Vec2::new(5.0, 0.0)), Vec2::new(2.0, 0.0));
+        assert_eq!(v.reject(Vec2::new(5.0, 0.0)), Vec2::new(0.0, -3.0));
+        assert_close(
+            Vec2::new(1.0, 0.0).project(Vec2::new(1.0, 1.0)),
+            Vec2::new(0.5, 0.5),
+        );
+        assert_eq!(
+            Vec3::new(1.0, -1.0, 2.0).reflect(Vec3::Z),
+            Vec3::new(1.0, -1.0, -2.0)
+        );
+    }
+
+    #[test]
+    fn swizzles_and_conversions() {
+        let v = Vec4::new(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(v.xy(), Vec2::new(1.0, 2.0));
+        assert_eq!(v.truncate(), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(v.xyz().zyx(), Vec3::new(3.0, 2.0, 1.0));
+        assert_eq!(v.xyz().xz(), Vec2::new(1.0, 3.0));
+        assert_eq!(v.xyz().yz(), Vec2::new(2.0, 3.0));
+        assert_eq!(v.xy().yx(), Vec2::new(2.0, 1.0));
+        assert_eq!(
+            v.xy().extend(5.0).extend(6.0),
+            Vec4::new(1.0, 2.0, 5.0, 6.0)
+        );
+
+        assert_eq!(Vec2::from([1.0, 2.0]), Vec2::new(1.0, 2.0));
+        assert_eq!(<[f32; 3]>::from(Vec3::Z), [0.0, 0.0, 1.0]);
+        assert_eq!(Vec3::from((1.0, 2.0, 3.0)), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(<(f32, f32)>::from(Vec2::Y), (0.0, 1.0));
+
+        assert_eq!(Vec2::new(2.9, -2.9).as_ivec2(), IVec2::new(2, -2));
+        assert_eq!(Vec2::new(2.9, -2.9).as_uvec2(), UVec2::new(2, 0));
+    }
+}
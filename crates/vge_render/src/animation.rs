@@ -0,0 +1,453 @@
+use std::{collections::HashMap, ops::Range};
+
+use vge_math::{Rect, Vec2};
+
+use crate::{
+    atlas::{AtlasRegion, TextureAtlas},
+    mesh::{DrawSprite, Sprite},
+};
+
+/// Shortest a frame can last, keeps zero length frames from stalling [`AnimatedSprite::update`]
+const MIN_FRAME_DURATION: f32 = 0.001;
+
+/// Frames of an animation, clips refer to them by index
+#[derive(Clone)]
+pub struct SpriteSheet {
+    frames: Vec<Sprite>,
+}
+
+impl SpriteSheet {
+    pub fn new(frames: Vec<Sprite>) -> Self {
+        Self { frames }
+    }
+
+    /// Cuts `sprite` into `frame_size` cells, numbered left to right and then top to bottom,
+    /// cells that don't fit whole are left out
+    pub fn from_grid(sprite: &Sprite, frame_size: (u32, u32)) -> Self {
+        let (width, height) = sprite.size();
+        let columns = width.checked_div(frame_size.0).unwrap_or(0);
+        let rows = height.checked_div(frame_size.1).unwrap_or(0);
+        let size = Vec2::new(frame_size.0 as f32, frame_size.1 as f32);
+
+        let frames = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                let min = Vec2::new(column as f32, row as f32) * size;
+                sprite.region(Rect::new(min, min + size))
+            })
+            .collect();
+        Self { frames }
+    }
+
+    /// One frame per region, in order
+    pub fn from_atlas(
+        atlas: &TextureAtlas,
+        regions: impl IntoIterator<Item = AtlasRegion>,
+    ) -> Self {
+        Self {
+            frames: regions
+                .into_iter()
+                .map(|region| atlas.sprite(region))
+                .collect(),
+        }
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&Sprite> {
+        self.frames.get(index)
+    }
+
+    pub fn frames(&self) -> &[Sprite] {
+        &self.frames
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// What a clip does once it reaches its last frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayMode {
+    /// Starts over from the first frame
+    #[default]
+    Loop,
+    /// Plays backwards to the first frame and then forwards again
+    PingPong,
+    /// Stays on the last frame, see [`AnimatedSprite::is_finished`]
+    Once,
+}
+
+/// Named sequence of sheet frames, each shown for its own duration
+#[derive(Clone, Debug, Default)]
+pub struct Clip {
+    /// Sheet frame and how long it is shown in seconds
+    frames: Vec<(usize, f32)>,
+    mode: PlayMode,
+    /// Fired when the clip reaches the frame at that position
+    events: Vec<(usize, String)>,
+}
+
+impl Clip {
+    /// Shows every frame for the same time
+    pub fn new(frames: impl IntoIterator<Item = usize>, fps: f32) -> Self {
+        let duration = 1.0 / fps;
+        Self::with_durations(frames.into_iter().map(|frame| (frame, duration)))
+    }
+
+    /// Consecutive sheet frames, shown for the same time
+    pub fn range(frames: Range<usize>, fps: f32) -> Self {
+        Self::new(frames, fps)
+    }
+
+    /// Sheet frames with how long to show each of them in seconds
+    pub fn with_durations(frames: impl IntoIterator<Item = (usize, f32)>) -> Self {
+        Self {
+            frames: frames
+                .into_iter()
+                .map(|(frame, duration)| (frame, duration.max(MIN_FRAME_DURATION)))
+                .collect(),
+            mode: PlayMode::Loop,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn mode(mut self, mode: PlayMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Fires `name` whenever playback reaches the frame at `position` within the clip
+    pub fn event(mut self, position: usize, name: impl Into<String>) -> Self {
+        self.events.push((position, name.into()));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Seconds it takes to play through once
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|(_, duration)| duration).sum()
+    }
+}
+
+/// Plays clips of a [`SpriteSheet`], call [`AnimatedSprite::update`] every step
+#[derive(Clone)]
+pub struct AnimatedSprite {
+    sheet: SpriteSheet,
+    clips: HashMap<String, Clip>,
+    clip: Option<String>,
+    /// Position within the clip, or the sheet frame without a clip
+    frame: usize,
+    /// Seconds spent on the current frame
+    time: f32,
+    speed: f32,
+    backwards: bool,
+    paused: bool,
+    finished: bool,
+    /// Whether the events of the first frame went out yet
+    started: bool,
+    /// Fired during the last update
+    events: Vec<String>,
+}
+
+impl AnimatedSprite {
+    pub fn new(sheet: SpriteSheet) -> Self {
+        Self {
+            sheet,
+            clips: HashMap::new(),
+            clip: None,
+            frame: 0,
+            time: 0.0,
+            speed: 1.0,
+            backwards: false,
+            paused: false,
+            finished: false,
+            started: false,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn with_clip(mut self, name: impl Into<String>, clip: Clip) -> Self {
+        self.add_clip(name, clip);
+        self
+    }
+
+    pub fn add_clip(&mut self, name: impl Into<String>, clip: Clip) {
+        self.clips.insert(name.into(), clip);
+    }
+
+    pub fn sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+
+    /// Switches to the clip called `name`, keeps going if it's already playing
+    pub fn play(&mut self, name: &str) {
+        if self.clip.as_deref() != Some(name) && self.clips.contains_key(name) {
+            self.clip = Some(name.to_owned());
+            self.restart();
+        }
+        self.paused = false;
+    }
+
+    /// Back to the first frame of the current clip
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.time = 0.0;
+        self.backwards = false;
+        self.finished = false;
+        self.started = false;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Whether a [`PlayMode::Once`] clip reached its end
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Name of the clip that is playing
+    pub fn clip(&self) -> Option<&str> {
+        self.clip.as_deref()
+    }
+
+    /// Playback rate, 2 plays twice as fast and 0 holds the current frame
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Position within the current clip, or the sheet frame when there's no clip
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Jumps to a position within the current clip, events on it don't fire
+    pub fn set_frame(&mut self, frame: usize) {
+        self.frame = frame.min(self.frame_count().saturating_sub(1));
+        self.time = 0.0;
+        self.finished = false;
+        self.started = true;
+    }
+
+    /// Names of the frame events fired by the last [`AnimatedSprite::update`]
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+
+    /// Whether `name` fired during the last [`AnimatedSprite::update`]
+    pub fn fired(&self, name: &str) -> bool {
+        self.events.iter().any(|event| event == name)
+    }
+
+    /// Advances playback by `delta` seconds, call it from `App::step` with `Ctx::delta`
+    pub fn update(&mut self, delta: f32) {
+        self.events.clear();
+        if self.paused || self.finished {
+            return;
+        }
+        let Some(clip) = self.clip.as_ref().and_then(|name| self.clips.get(name)) else {
+            return;
+        };
+        if clip.frames.is_empty() {
+            return;
+        }
+
+        if !self.started {
+            self.started = true;
+            Self::fire(&mut self.events, clip, self.frame);
+        }
+
+        self.time += delta * self.speed;
+        while self.time >= clip.frames[self.frame].1 {
+            self.time -= clip.frames[self.frame].1;
+
+            let last = clip.frames.len() - 1;
+            self.frame = match clip.mode {
+                PlayMode::Loop => (self.frame + 1) % clip.frames.len(),
+                PlayMode::Once if self.frame == last => {
+                    self.time = 0.0;
+                    self.finished = true;
+                    return;
+                }
+                PlayMode::Once => self.frame + 1,
+                PlayMode::PingPong if last == 0 => 0,
+                PlayMode::PingPong => {
+                    if (self.backwards && self.frame == 0)
+                        || (!self.backwards && self.frame == last)
+                    {
+                        self.backwards = !self.backwards;
+                    }
+                    if self.backwards {
+                        self.frame - 1
+                    } else {
+                        self.frame + 1
+                    }
+                }
+            };
+
+            Self::fire(&mut self.events, clip, self.frame);
+        }
+    }
+
+    fn fire(events: &mut Vec<String>, clip: &Clip, frame: usize) {
+        events.extend(
+            clip.events
+                .iter()
+                .filter(|(position, _)| *position == frame)
+                .map(|(_, name)| name.clone()),
+        );
+    }
+
+    /// Sprite of the frame that is showing
+    pub fn sprite(&self) -> Option<&Sprite> {
+        let index = match self.clip.as_ref().and_then(|name| self.clips.get(name)) {
+            Some(clip) => clip.frames.get(self.frame)?.0,
+            None => self.frame,
+        };
+        self.sheet.frame(index)
+    }
+
+    /// Current frame drawn at `position`, `None` if the sheet has no such frame
+    pub fn at(&self, position: Vec2) -> Option<DrawSprite> {
+        self.sprite().map(|sprite| sprite.at(position))
+    }
+
+    fn frame_count(&self) -> usize {
+        match self.clip.as_ref().and_then(|name| self.clips.get(name)) {
+            Some(clip) => clip.len(),
+            None => self.sheet.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three frames a quarter of a second each, with events on the first and last
+    fn sprite(mode: PlayMode) -> AnimatedSprite {
+        let clip = Clip::range(0..3, 4.0)
+            .mode(mode)
+            .event(0, "start")
+            .event(2, "end");
+        let mut sprite = AnimatedSprite::new(SpriteSheet::new(Vec::new())).with_clip("clip", clip);
+        sprite.play("clip");
+        sprite
+    }
+
+    /// Frame and fired events, comma separated, after each of `steps` updates by `delta`
+    fn steps(sprite: &mut AnimatedSprite, steps: usize, delta: f32) -> Vec<(usize, String)> {
+        (0..steps)
+            .map(|_| {
+                sprite.update(delta);
+                (sprite.frame(), sprite.events().join(","))
+            })
+            .collect()
+    }
+
+    fn expected(steps: &[(usize, &str)]) -> Vec<(usize, String)> {
+        steps
+            .iter()
+            .map(|(frame, events)| (*frame, events.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn loops() {
+        let mut sprite = sprite(PlayMode::Loop);
+        assert_eq!(
+            steps(&mut sprite, 6, 0.25),
+            expected(&[
+                (1, "start"),
+                (2, "end"),
+                (0, "start"),
+                (1, ""),
+                (2, "end"),
+                (0, "start"),
+            ])
+        );
+        assert!(!sprite.is_finished());
+    }
+
+    #[test]
+    fn ping_pongs() {
+        let mut sprite = sprite(PlayMode::PingPong);
+        let frames: Vec<usize> = steps(&mut sprite, 8, 0.25)
+            .into_iter()
+            .map(|(frame, _)| frame)
+            .collect();
+        assert_eq!(frames, [1, 2, 1, 0, 1, 2, 1, 0]);
+
+        // the ends only fire once when turning around
+        let mut sprite = self::sprite(PlayMode::PingPong);
+        sprite.update(1.0);
+        assert_eq!(sprite.frame(), 0);
+        assert_eq!(sprite.events(), ["start", "end", "start"]);
+    }
+
+    #[test]
+    fn plays_once() {
+        let mut sprite = sprite(PlayMode::Once);
+        assert_eq!(
+            steps(&mut sprite, 4, 0.25),
+            expected(&[(1, "start"), (2, "end"), (2, ""), (2, "")])
+        );
+        assert!(sprite.is_finished());
+
+        sprite.restart();
+        assert!(!sprite.is_finished());
+        sprite.update(0.0);
+        assert_eq!(
+            (sprite.frame(), sprite.events()),
+            (0, &["start".to_owned()][..])
+        );
+    }
+
+    #[test]
+    fn speed_and_long_steps() {
+        let mut sprite = sprite(PlayMode::Loop);
+        sprite.set_speed(2.0);
+        sprite.update(0.25);
+        assert_eq!(sprite.frame(), 2);
+        assert_eq!(sprite.events(), ["start", "end"]);
+
+        // leftover time carries over to the next update
+        sprite.set_speed(1.0);
+        sprite.update(0.125);
+        assert_eq!(sprite.frame(), 2);
+        sprite.update(0.125);
+        assert_eq!(sprite.frame(), 0);
+
+        sprite.update(1.0);
+        assert_eq!(sprite.frame(), 1);
+        assert_eq!(sprite.events(), ["end", "start"]);
+
+        sprite.set_speed(0.0);
+        sprite.update(10.0);
+        assert_eq!(sprite.frame(), 1);
+        assert!(sprite.events().is_empty());
+    }
+}
@@ -0,0 +1,809 @@
+use std::{io::Read, path::Path};
+
+use image::{DynamicImage, RgbaImage};
+use vge_math::{Rect, Vec2};
+
+use crate::{
+    Gfx, RenderError,
+    animation::{AnimatedSprite, Clip, PlayMode, SpriteSheet},
+    atlas::AtlasBuilder,
+    reader::Reader,
+};
+
+const HEADER_MAGIC: u16 = 0xa5e0;
+const FRAME_MAGIC: u16 = 0xf1fa;
+
+const CHUNK_OLD_PALETTE: u16 = 0x0004;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+const CHUNK_SLICE: u16 = 0x2022;
+
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_BACKGROUND: u16 = 8;
+const LAYER_REFERENCE: u16 = 64;
+const LAYER_TYPE_GROUP: u16 = 1;
+const LAYER_TYPE_TILEMAP: u16 = 2;
+
+/// Header flag saying layer opacity is stored and should be used
+const HEADER_LAYER_OPACITY: u32 = 1;
+
+const SLICE_NINE_PATCH: u32 = 1;
+const SLICE_PIVOT: u32 = 2;
+
+/// Whether `bytes` look like an Aseprite file
+pub(crate) fn is_aseprite(bytes: &[u8]) -> bool {
+    bytes.get(4..6) == Some(&HEADER_MAGIC.to_le_bytes())
+}
+
+/// Single frame with every visible layer flattened into it
+#[derive(Clone, Debug)]
+pub struct AsepriteFrame {
+    pub image: RgbaImage,
+    /// In seconds
+    pub duration: f32,
+}
+
+/// Direction a tag plays its frames in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+/// Named range of frames, imported as an animation clip
+#[derive(Clone, Debug)]
+pub struct AsepriteTag {
+    pub name: String,
+    /// First and last frame, both inclusive
+    pub from: usize,
+    pub to: usize,
+    pub direction: TagDirection,
+    /// Times to play through, 0 repeats forever
+    pub repeat: u16,
+}
+
+impl AsepriteTag {
+    /// Clip playing the tag with the frame durations of `frames`,
+    /// a limited number of repeats plays through once and stops
+    pub fn clip(&self, frames: &[AsepriteFrame]) -> Clip {
+        let forward: Vec<usize> = (self.from..=self.to).collect();
+        let backward: Vec<usize> = forward.iter().rev().copied().collect();
+        let (first, second) = match self.direction {
+            TagDirection::Forward | TagDirection::PingPong => (&forward, &backward),
+            TagDirection::Reverse | TagDirection::PingPongReverse => (&backward, &forward),
+        };
+        let ping_pong = matches!(
+            self.direction,
+            TagDirection::PingPong | TagDirection::PingPongReverse
+        );
+
+        let (sequence, mode) = match (self.repeat, ping_pong) {
+            (0, true) => (first.clone(), PlayMode::PingPong),
+            (0, false) => (first.clone(), PlayMode::Loop),
+            (repeat, _) => {
+                let mut sequence = first.clone();
+                for pass in 1..repeat {
+                    if !ping_pong {
+                        sequence.extend(first);
+                        continue;
+                    }
+                    // every pass turns around, without showing the turning frame twice
+                    let pass = if pass % 2 == 1 { second } else { first };
+                    sequence.extend(pass.iter().skip(1));
+                }
+                (sequence, PlayMode::Once)
+            }
+        };
+
+        Clip::with_durations(sequence.into_iter().map(|frame| {
+            let duration = frames.get(frame).map_or(0.0, |frame| frame.duration);
+            (frame, duration)
+        }))
+        .mode(mode)
+    }
+}
+
+/// Named region of the sprite, keyed by the frames it changes on
+#[derive(Clone, Debug)]
+pub struct AsepriteSlice {
+    pub name: String,
+    pub keys: Vec<SliceKey>,
+}
+
+impl AsepriteSlice {
+    /// Key in effect on `frame`
+    pub fn key(&self, frame: usize) -> Option<&SliceKey> {
+        self.keys.iter().rev().find(|key| key.frame <= frame)
+    }
+}
+
+/// Shape of a slice from `frame` onwards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SliceKey {
+    pub frame: usize,
+    /// In texels with (0, 0) at the top left of the sprite
+    pub bounds: Rect,
+    /// 9-slice center, in texels relative to the top left of `bounds`
+    pub center: Option<Rect>,
+    /// In texels relative to the top left of `bounds`
+    pub pivot: Option<Vec2>,
+}
+
+impl SliceKey {
+    /// Pivot as a [`DrawSprite::origin`](crate::mesh::DrawSprite::origin)
+    /// for a sprite covering the slice
+    pub fn origin(&self) -> Option<Vec2> {
+        let size = self.bounds.size();
+        self.pivot
+            .map(|pivot| Vec2::new(pivot.x / size.x, 1.0 - pivot.y / size.y))
+    }
+}
+
+/// Aseprite `.aseprite` or `.ase` file with its visible layers flattened
+///
+/// Every layer is composited with the normal blend mode, tilemap layers are left out
+#[derive(Clone, Debug)]
+pub struct Aseprite {
+    pub width: u32,
+    pub height: u32,
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+    pub slices: Vec<AsepriteSlice>,
+}
+
+struct Layer {
+    visible: bool,
+    background: bool,
+    opacity: u8,
+}
+
+/// Pixels of a cel before they are converted to rgba
+enum CelPixels {
+    Image {
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    },
+    /// Same pixels as the cel of this layer on another frame
+    Linked(usize),
+}
+
+struct Cel {
+    layer: usize,
+    x: i32,
+    y: i32,
+    opacity: u8,
+    z_index: i16,
+    pixels: CelPixels,
+}
+
+impl Aseprite {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RenderError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RenderError> {
+        let mut reader = Reader::new(bytes, RenderError::Aseprite);
+        reader.skip(4)?;
+        if reader.u16()? != HEADER_MAGIC {
+            return Err(RenderError::Aseprite("not an aseprite file".into()));
+        }
+        let frame_count = reader.u16()? as usize;
+        let width = reader.u16()? as u32;
+        let height = reader.u16()? as u32;
+        let depth = reader.u16()?;
+        let flags = reader.u32()?;
+        reader.skip(10)?;
+        let transparent_index = reader.u8()?;
+        reader.skip(128 - 29)?;
+
+        if frame_count == 0 {
+            return Err(RenderError::Aseprite("no frames".into()));
+        }
+        if width == 0 || height == 0 {
+            return Err(RenderError::Aseprite("empty canvas".into()));
+        }
+        if !matches!(depth, 8 | 16 | 32) {
+            return Err(RenderError::Aseprite(format!(
+                "unsupported color depth {depth}"
+            )));
+        }
+
+        let mut layers = Vec::new();
+        // effective visibility of the last group on every nesting level
+        let mut groups: Vec<bool> = Vec::new();
+        let mut palette = vec![[0u8; 4]; 256];
+        let mut durations = Vec::with_capacity(frame_count);
+        let mut cels: Vec<Vec<Cel>> = Vec::with_capacity(frame_count);
+        let mut tags = Vec::new();
+        let mut slices = Vec::new();
+
+        for _ in 0..frame_count {
+            let size = reader.u32()? as usize;
+            let mut frame = reader.sub(size.saturating_sub(4))?;
+            if frame.u16()? != FRAME_MAGIC {
+                return Err(RenderError::Aseprite("corrupt frame header".into()));
+            }
+            let old_chunks = frame.u16()? as u32;
+            durations.push(frame.u16()? as f32 / 1000.0);
+            frame.skip(2)?;
+            let chunks = match frame.u32()? {
+                0 => old_chunks,
+                chunks => chunks,
+            };
+
+            let mut frame_cels = Vec::new();
+            for _ in 0..chunks {
+                let size = frame.u32()? as usize;
+                let kind = frame.u16()?;
+                let mut chunk = frame.sub(size.saturating_sub(6))?;
+
+                match kind {
+                    CHUNK_LAYER => {
+                        let layer_flags = chunk.u16()?;
+                        let layer_type = chunk.u16()?;
+                        let level = chunk.u16()? as usize;
+                        chunk.skip(6)?;
+                        let opacity = chunk.u8()?;
+
+                        let parent_visible = level == 0 || groups.get(level - 1) == Some(&true);
+                        let visible = layer_flags & LAYER_VISIBLE != 0
+                            && layer_flags & LAYER_REFERENCE == 0
+                            && parent_visible;
+                        groups.truncate(level);
+                        groups.push(visible);
+
+                        layers.push(Layer {
+                            // groups and tilemaps have no pixels of their own to draw
+                            visible: visible
+                                && layer_type != LAYER_TYPE_GROUP
+                                && layer_type != LAYER_TYPE_TILEMAP,
+                            background: layer_flags & LAYER_BACKGROUND != 0,
+                            opacity: if flags & HEADER_LAYER_OPACITY != 0 {
+                                opacity
+                            } else {
+                                255
+                            },
+                        });
+                    }
+                    CHUNK_CEL => {
+                        let layer = chunk.u16()? as usize;
+                        let x = chunk.i16()? as i32;
+                        let y = chunk.i16()? as i32;
+                        let opacity = chunk.u8()?;
+                        let cel_type = chunk.u16()?;
+                        let z_index = chunk.i16()?;
+                        chunk.skip(5)?;
+
+                        let pixels = match cel_type {
+                            0 => CelPixels::Image {
+                                width: chunk.u16()? as u32,
+                                height: chunk.u16()? as u32,
+                                data: chunk.rest().to_vec(),
+                            },
+                            1 => CelPixels::Linked(chunk.u16()? as usize),
+                            2 => {
+                                let (width, height) = (chunk.u16()? as u32, chunk.u16()? as u32);
+                                // never inflate past what the cel can use
+                                let len = width as u64 * height as u64 * depth as u64 / 8;
+                                let mut data = Vec::new();
+                                flate2::read::ZlibDecoder::new(chunk.rest())
+                                    .take(len)
+                                    .read_to_end(&mut data)?;
+                                CelPixels::Image {
+                                    width,
+                                    height,
+                                    data,
+                                }
+                            }
+                            // tilemaps
+                            _ => continue,
+                        };
+                        frame_cels.push(Cel {
+                            layer,
+                            x,
+                            y,
+                            opacity,
+                            z_index,
+                            pixels,
+                        });
+                    }
+                    CHUNK_PALETTE => {
+                        // new palette size, cels index it with a byte so 256 entries always do
+                        chunk.skip(4)?;
+                        let first = chunk.u32()? as usize;
+                        let last = chunk.u32()? as usize;
+                        chunk.skip(8)?;
+                        for index in first..=last {
+                            let entry_flags = chunk.u16()?;
+                            let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?];
+                            if entry_flags & 1 != 0 {
+                                chunk.string()?;
+                            }
+                            if let Some(entry) = palette.get_mut(index) {
+                                *entry = color;
+                            }
+                        }
+                    }
+                    CHUNK_OLD_PALETTE if depth == 8 => {
+                        let mut index = 0;
+                        for _ in 0..chunk.u16()? {
+                            index += chunk.u8()? as usize;
+                            let count = match chunk.u8()? {
+                                0 => 256,
+                                count => count as usize,
+                            };
+                            for _ in 0..count {
+                                let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, 255];
+                                if let Some(entry) = palette.get_mut(index) {
+                                    *entry = color;
+                                }
+                                index += 1;
+                            }
+                        }
+                    }
+                    CHUNK_TAGS => {
+                        let count = chunk.u16()?;
+                        chunk.skip(8)?;
+                        for _ in 0..count {
+                            let from = chunk.u16()? as usize;
+                            let to = chunk.u16()? as usize;
+                            let direction = match chunk.u8()? {
+                                1 => TagDirection::Reverse,
+                                2 => TagDirection::PingPong,
+                                3 => TagDirection::PingPongReverse,
+                                _ => TagDirection::Forward,
+                            };
+                            let repeat = chunk.u16()?;
+                            chunk.skip(10)?;
+                            tags.push(AsepriteTag {
+                                name: chunk.string()?,
+                                from,
+                                to,
+                                direction,
+                                repeat,
+                            });
+                        }
+                    }
+                    CHUNK_SLICE => {
+                        let count = chunk.u32()?;
+                        let slice_flags = chunk.u32()?;
+                        chunk.skip(4)?;
+                        let name = chunk.string()?;
+
+                        let mut keys = Vec::new();
+                        for _ in 0..count {
+                            let frame = chunk.u32()? as usize;
+                            let bounds = read_rect(&mut chunk)?;
+                            let center = if slice_flags & SLICE_NINE_PATCH != 0 {
+                                Some(read_rect(&mut chunk)?)
+                            } else {
+                                None
+                            };
+                            let pivot = if slice_flags & SLICE_PIVOT != 0 {
+                                Some(Vec2::new(chunk.i32()? as f32, chunk.i32()? as f32))
+                            } else {
+                                None
+                            };
+                            keys.push(SliceKey {
+                                frame,
+                                bounds,
+                                center,
+                                pivot,
+                            });
+                        }
+                        slices.push(AsepriteSlice { name, keys });
+                    }
+                    // color profiles, user data, tilesets and such
+                    _ => {}
+                }
+            }
+            cels.push(frame_cels);
+        }
+
+        let decoder = PixelDecoder {
+            depth,
+            palette,
+            transparent_index,
+        };
+        let frames = (0..frame_count)
+            .map(|frame| AsepriteFrame {
+                image: flatten(&layers, &cels, frame, width, height, &decoder),
+                duration: durations[frame],
+            })
+            .collect();
+
+        Ok(Self {
+            width,
+            height,
+            frames,
+            tags,
+            slices,
+        })
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&AsepriteTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    pub fn slice(&self, name: &str) -> Option<&AsepriteSlice> {
+        self.slices.iter().find(|slice| slice.name == name)
+    }
+
+    /// Every tag as a clip named after it
+    pub fn clips(&self) -> impl Iterator<Item = (String, Clip)> + '_ {
+        self.tags
+            .iter()
+            .map(|tag| (tag.name.clone(), tag.clip(&self.frames)))
+    }
+
+    /// Uploads the frames packed into an atlas, in order
+    pub fn sprite_sheet(&self, gfx: &Gfx) -> Result<SpriteSheet, RenderError> {
+        let mut builder = AtlasBuilder::new();
+        let regions: Vec<_> = self
+            .frames
+            .iter()
+            .map(|frame| builder.add(DynamicImage::ImageRgba8(frame.image.clone())))
+            .collect();
+        let atlas = builder.build(gfx)?;
+        Ok(SpriteSheet::from_atlas(&atlas, regions))
+    }
+
+    /// Sprite sheet with a clip for every tag
+    pub fn animated_sprite(&self, gfx: &Gfx) -> Result<AnimatedSprite, RenderError> {
+        let mut sprite = AnimatedSprite::new(self.sprite_sheet(gfx)?);
+        for (name, clip) in self.clips() {
+            sprite.add_clip(name, clip);
+        }
+        Ok(sprite)
+    }
+}
+
+fn read_rect(reader: &mut Reader) -> Result<Rect, RenderError> {
+    let min = Vec2::new(reader.i32()? as f32, reader.i32()? as f32);
+    let size = Vec2::new(reader.u32()? as f32, reader.u32()? as f32);
+    Ok(Rect::new(min, min + size))
+}
+
+/// Turns cel pixels of any color depth into rgba
+struct PixelDecoder {
+    depth: u16,
+    palette: Vec<[u8; 4]>,
+    transparent_index: u8,
+}
+
+impl PixelDecoder {
+    fn pixel(&self, data: &[u8], index: usize, background: bool) -> [u8; 4] {
+        match self.depth {
+            32 => data
+                .get(index * 4..index * 4 + 4)
+                .map_or([0; 4], |p| [p[0], p[1], p[2], p[3]]),
+            16 => data
+                .get(index * 2..index * 2 + 2)
+                .map_or([0; 4], |p| [p[0], p[0], p[0], p[1]]),
+            _ => match data.get(index) {
+                // the transparent index is a real color on background layers
+                Some(&i) if i != self.transparent_index || background => self.palette[i as usize],
+                _ => [0; 4],
+            },
+        }
+    }
+}
+
+/// Composites the visible layers of `frame` bottom to top
+fn flatten(
+    layers: &[Layer],
+    cels: &[Vec<Cel>],
+    frame: usize,
+    width: u32,
+    height: u32,
+    decoder: &PixelDecoder,
+) -> RgbaImage {
+    let mut image = RgbaImage::new(width, height);
+
+    let mut order: Vec<&Cel> = cels[frame].iter().collect();
+    order.sort_by_key(|cel| (cel.layer as i32 + cel.z_index as i32, cel.z_index));
+
+    for cel in order {
+        let Some(layer) = layers.get(cel.layer).filter(|layer| layer.visible) else {
+            continue;
+        };
+
+        // linked cels share everything with the original, position included
+        let cel = match cel.pixels {
+            CelPixels::Linked(other) => cels
+                .get(other)
+                .and_then(|cels| cels.iter().find(|linked| linked.layer == cel.layer)),
+            _ => Some(cel),
+        };
+        let Some(cel) = cel else {
+            continue;
+        };
+        let CelPixels::Image {
+            width: cel_width,
+            height: cel_height,
+            data,
+        } = &cel.pixels
+        else {
+            continue;
+        };
+
+        // only the part of the cel that lands on the canvas
+        let visible = |position: i32, size: u32, canvas: u32| {
+            let start = (-(position as i64)).clamp(0, size as i64);
+            let end = (canvas as i64 - position as i64).clamp(start, size as i64);
+            start as u32..end as u32
+        };
+
+        let opacity = cel.opacity as f32 / 255.0 * layer.opacity as f32 / 255.0;
+        for y in visible(cel.y, *cel_height, height) {
+            for x in visible(cel.x, *cel_width, width) {
+                let (target_x, target_y) = (cel.x + x as i32, cel.y + y as i32);
+                let index = (y * cel_width + x) as usize;
+                let source = decoder.pixel(data, index, layer.background);
+                let target = image.get_pixel_mut(target_x as u32, target_y as u32);
+                target.0 = blend(target.0, source, opacity);
+            }
+        }
+    }
+
+    image
+}
+
+/// Normal blend mode, `source` drawn over `target`
+fn blend(target: [u8; 4], source: [u8; 4], opacity: f32) -> [u8; 4] {
+    let source_alpha = source[3] as f32 / 255.0 * opacity;
+    if source_alpha <= 0.0 {
+        return target;
+    }
+
+    let target_alpha = target[3] as f32 / 255.0;
+    let alpha = source_alpha + target_alpha * (1.0 - source_alpha);
+    let channel = |i: usize| {
+        let color = source[i] as f32 * source_alpha
+            + target[i] as f32 * target_alpha * (1.0 - source_alpha);
+        (color / alpha).round() as u8
+    };
+    [
+        channel(0),
+        channel(1),
+        channel(2),
+        (alpha * 255.0).round() as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn string(bytes: &mut Vec<u8>, value: &str) {
+        bytes.extend((value.len() as u16).to_le_bytes());
+        bytes.extend(value.as_bytes());
+    }
+
+    fn chunk(kind: u16, data: &[u8]) -> Vec<u8> {
+        let mut bytes = (data.len() as u32 + 6).to_le_bytes().to_vec();
+        bytes.extend(kind.to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    fn frame(duration_ms: u16, chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut bytes = (body.len() as u32 + 16).to_le_bytes().to_vec();
+        bytes.extend(FRAME_MAGIC.to_le_bytes());
+        bytes.extend((chunks.len() as u16).to_le_bytes());
+        bytes.extend(duration_ms.to_le_bytes());
+        bytes.extend([0; 2]);
+        bytes.extend((chunks.len() as u32).to_le_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    fn file(size: (u16, u16), depth: u16, frames: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = frames.concat();
+        let mut bytes = (body.len() as u32 + 128).to_le_bytes().to_vec();
+        bytes.extend(HEADER_MAGIC.to_le_bytes());
+        bytes.extend((frames.len() as u16).to_le_bytes());
+        bytes.extend(size.0.to_le_bytes());
+        bytes.extend(size.1.to_le_bytes());
+        bytes.extend(depth.to_le_bytes());
+        bytes.extend(HEADER_LAYER_OPACITY.to_le_bytes());
+        bytes.extend([0; 10]);
+        // transparent index
+        bytes.push(0);
+        bytes.resize(128, 0);
+        bytes.extend(body);
+        bytes
+    }
+
+    fn layer(flags: u16, opacity: u8, name: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(flags.to_le_bytes());
+        data.extend([0; 10]);
+        data.extend([opacity, 0, 0, 0]);
+        string(&mut data, name);
+        chunk(CHUNK_LAYER, &data)
+    }
+
+    fn cel_header(layer: u16, position: (i16, i16), cel_type: u16) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(layer.to_le_bytes());
+        data.extend(position.0.to_le_bytes());
+        data.extend(position.1.to_le_bytes());
+        data.push(255);
+        data.extend(cel_type.to_le_bytes());
+        data.extend([0; 7]);
+        data
+    }
+
+    fn raw_cel(layer: u16, position: (i16, i16), size: (u16, u16), pixels: &[u8]) -> Vec<u8> {
+        let mut data = cel_header(layer, position, 0);
+        data.extend(size.0.to_le_bytes());
+        data.extend(size.1.to_le_bytes());
+        data.extend(pixels);
+        chunk(CHUNK_CEL, &data)
+    }
+
+    fn compressed_cel(layer: u16, size: (u16, u16), pixels: &[u8]) -> Vec<u8> {
+        let mut data = cel_header(layer, (0, 0), 2);
+        data.extend(size.0.to_le_bytes());
+        data.extend(size.1.to_le_bytes());
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(pixels).unwrap();
+        data.extend(encoder.finish().unwrap());
+        chunk(CHUNK_CEL, &data)
+    }
+
+    fn linked_cel(layer: u16, frame: u16) -> Vec<u8> {
+        let mut data = cel_header(layer, (0, 0), 1);
+        data.extend(frame.to_le_bytes());
+        chunk(CHUNK_CEL, &data)
+    }
+
+    fn tags() -> Vec<u8> {
+        let mut data = 1u16.to_le_bytes().to_vec();
+        data.extend([0; 8]);
+        data.extend(0u16.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.push(2);
+        data.extend(3u16.to_le_bytes());
+        data.extend([0; 10]);
+        string(&mut data, "walk");
+        chunk(CHUNK_TAGS, &data)
+    }
+
+    fn slice() -> Vec<u8> {
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend((SLICE_NINE_PATCH | SLICE_PIVOT).to_le_bytes());
+        data.extend([0; 4]);
+        string(&mut data, "hitbox");
+        data.extend(1u32.to_le_bytes());
+        for value in [1i32, 0, 3, 2, 1, 1, 1, 1, 0, 2] {
+            data.extend(value.to_le_bytes());
+        }
+        chunk(CHUNK_SLICE, &data)
+    }
+
+    fn rgba_file() -> Vec<u8> {
+        let red_blue = [255, 0, 0, 255, 0, 0, 255, 128];
+        file(
+            (3, 2),
+            32,
+            &[
+                frame(
+                    100,
+                    &[
+                        layer(LAYER_VISIBLE, 255, "body"),
+                        layer(0, 255, "hidden"),
+                        tags(),
+                        slice(),
+                        raw_cel(0, (1, 1), (2, 1), &red_blue),
+                        raw_cel(1, (0, 0), (1, 1), &[0, 255, 0, 255]),
+                    ],
+                ),
+                frame(250, &[linked_cel(0, 0)]),
+            ],
+        )
+    }
+
+    #[test]
+    fn rgba_and_linked_cels() {
+        let aseprite = Aseprite::from_bytes(&rgba_file()).unwrap();
+        assert_eq!((aseprite.width, aseprite.height), (3, 2));
+        assert_eq!(aseprite.frames.len(), 2);
+        assert_eq!(aseprite.frames[0].duration, 0.1);
+        assert_eq!(aseprite.frames[1].duration, 0.25);
+
+        let image = &aseprite.frames[0].image;
+        // the hidden layer's green pixel isn't drawn
+        assert_eq!(image.get_pixel(0, 0).0, [0; 4]);
+        assert_eq!(image.get_pixel(1, 1).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(2, 1).0, [0, 0, 255, 128]);
+        assert_eq!(&aseprite.frames[1].image, image);
+    }
+
+    #[test]
+    fn tags_and_slices() {
+        let aseprite = Aseprite::from_bytes(&rgba_file()).unwrap();
+
+        let tag = aseprite.tag("walk").unwrap();
+        assert_eq!((tag.from, tag.to), (0, 1));
+        assert_eq!(tag.direction, TagDirection::PingPong);
+        assert_eq!(tag.repeat, 3);
+
+        let key = aseprite.slice("hitbox").unwrap().key(5).unwrap();
+        assert_eq!(key.frame, 1);
+        assert_eq!(
+            key.bounds,
+            Rect::new(Vec2::new(1.0, 0.0), Vec2::new(4.0, 2.0))
+        );
+        assert_eq!(key.center, Some(Rect::new(Vec2::ONE, Vec2::splat(2.0))));
+        assert_eq!(key.pivot, Some(Vec2::new(0.0, 2.0)));
+        assert!(aseprite.slice("hitbox").unwrap().key(0).is_none());
+    }
+
+    #[test]
+    fn indexed_cels() {
+        let mut palette = Vec::new();
+        palette.extend(256u32.to_le_bytes());
+        palette.extend(0u32.to_le_bytes());
+        palette.extend(1u32.to_le_bytes());
+        palette.extend([0; 8]);
+        palette.extend([0, 0, 9, 9, 9, 255]);
+        palette.extend([0, 0, 10, 20, 30, 255]);
+
+        let bytes = file(
+            (2, 1),
+            8,
+            &[frame(
+                100,
+                &[
+                    layer(LAYER_VISIBLE, 255, "layer"),
+                    chunk(CHUNK_PALETTE, &palette),
+                    compressed_cel(0, (2, 1), &[0, 1]),
+                ],
+            )],
+        );
+        let image = &Aseprite::from_bytes(&bytes).unwrap().frames[0].image;
+        // index 0 is the transparent one
+        assert_eq!(image.get_pixel(0, 0).0, [0; 4]);
+        assert_eq!(image.get_pixel(1, 0).0, [10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn invalid_files() {
+        let bytes = rgba_file();
+        for len in 0..bytes.len() {
+            assert!(Aseprite::from_bytes(&bytes[..len]).is_err(), "{len} bytes");
+        }
+
+        assert!(Aseprite::from_bytes(&file((0, 0), 32, &[frame(100, &[])])).is_err());
+        assert!(Aseprite::from_bytes(&file((1, 1), 24, &[frame(100, &[])])).is_err());
+        assert!(Aseprite::from_bytes(&file((1, 1), 32, &[])).is_err());
+    }
+
+    #[test]
+    fn huge_cels_only_touch_the_canvas() {
+        let layer = layer(LAYER_VISIBLE, 255, "layer");
+        let cel = raw_cel(0, (-1, -1), (u16::MAX, u16::MAX), &[255; 16]);
+        let bytes = file((3, 2), 32, &[frame(100, &[layer.clone(), cel])]);
+        let image = &Aseprite::from_bytes(&bytes).unwrap().frames[0].image;
+        // the texels past the data that's there come out transparent
+        assert_eq!(image.get_pixel(0, 0).0, [0; 4]);
+        assert_eq!(image.get_pixel(2, 1).0, [0; 4]);
+
+        let cel = compressed_cel(0, (u16::MAX, u16::MAX), &vec![7; 1 << 20]);
+        let bytes = file((3, 2), 32, &[frame(100, &[layer, cel])]);
+        let image = &Aseprite::from_bytes(&bytes).unwrap().frames[0].image;
+        assert_eq!(image.get_pixel(2, 1).0, [7; 4]);
+    }
+}
@@ -0,0 +1,451 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    panic::AssertUnwindSafe,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Condvar, Mutex, Once, RwLock, Weak,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+use crate::{
+    RenderError,
+    aseprite::Aseprite,
+    mesh::{Sprite, TexturedQuad},
+    pool::WorkerPool,
+    sampler::Sampler,
+    text::Font,
+    watch::{FileWatcher, POLL_INTERVAL},
+};
+
+/// Most threads loading assets at the same time
+const MAX_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Something [`Assets`] can load out of a file
+pub trait Asset: Send + Sync + Sized + 'static {
+    fn load(ctx: &LoadContext, bytes: &[u8]) -> Result<Self, RenderError>;
+}
+
+/// What an [`Asset`] gets to load itself with
+pub struct LoadContext<'a> {
+    pub(crate) device: &'a wgpu::Device,
+    pub(crate) queue: &'a wgpu::Queue,
+    pub(crate) texture_bind_group_layout: &'a wgpu::BindGroupLayout,
+    path: &'a Path,
+    sampler: Sampler,
+    mipmaps: bool,
+}
+
+impl LoadContext<'_> {
+    /// File being loaded, with the asset root in front
+    pub fn path(&self) -> &Path {
+        self.path
+    }
+
+    /// What textures get sampled with, see [`Assets::set_sampler`]
+    pub fn sampler(&self) -> Sampler {
+        self.sampler
+    }
+
+    /// Whether textures get a generated mip chain, see [`Assets::set_mipmaps`]
+    pub fn mipmaps(&self) -> bool {
+        self.mipmaps
+    }
+}
+
+impl Asset for Sprite {
+    fn load(ctx: &LoadContext, bytes: &[u8]) -> Result<Self, RenderError> {
+        let texture = TexturedQuad::from_bytes_sampled(
+            ctx.device,
+            ctx.queue,
+            ctx.texture_bind_group_layout,
+            bytes,
+            ctx.sampler,
+            ctx.mipmaps,
+            &ctx.path.to_string_lossy(),
+        )?;
+        Ok(Sprite::from_texture(Arc::new(texture)))
+    }
+}
+
+/// TrueType and OpenType fonts, or BMFont when the file ends in `.fnt`
+impl Asset for Font {
+    fn load(ctx: &LoadContext, bytes: &[u8]) -> Result<Self, RenderError> {
+        if ctx.path.extension().is_some_and(|ext| ext == "fnt") {
+            Font::bmfont(
+                ctx.device,
+                ctx.queue,
+                ctx.texture_bind_group_layout,
+                bytes,
+                ctx.path.parent().unwrap_or(Path::new("")),
+            )
+        } else {
+            Font::from_bytes(bytes)
+        }
+    }
+}
+
+impl Asset for Aseprite {
+    fn load(_ctx: &LoadContext, bytes: &[u8]) -> Result<Self, RenderError> {
+        Aseprite::from_bytes(bytes)
+    }
+}
+
+/// How far along a [`Handle`] is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadState {
+    Loading,
+    Loaded,
+    Failed,
+}
+
+enum SlotState<T> {
+    Loading,
+    Loaded(Arc<T>),
+    Failed(Arc<RenderError>),
+}
+
+/// Shared by every handle to the same file
+struct Slot<T> {
+    path: PathBuf,
+    state: Mutex<SlotState<T>>,
+    /// Signalled once loading is done, see [`Handle::wait`]
+    done: Condvar,
+}
+
+impl<T> Slot<T> {
+    fn finish(&self, state: SlotState<T>) {
+        *self.state.lock().unwrap() = state;
+        self.done.notify_all();
+    }
+}
+
+/// [`Slot`] of any asset type, lets the cache reload files without knowing what's in them
+trait AnySlot: Send + Sync {
+    fn path(&self) -> &Path;
+
+    /// Loads the file again and swaps the result in behind every handle
+    fn reload(&self, shared: &Shared);
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+impl<T: Asset> AnySlot for Slot<T> {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn reload(&self, shared: &Shared) {
+        match Assets::read::<T>(shared, &self.path) {
+            Ok(asset) => self.finish(SlotState::Loaded(Arc::new(asset))),
+            // a half written file shouldn't take away what was working
+            Err(error) if matches!(*self.state.lock().unwrap(), SlotState::Loaded(_)) => {
+                tracing::error!("could not reload {}: {error}", self.path.display());
+            }
+            Err(error) => self.finish(SlotState::Failed(Arc::new(error))),
+        }
+    }
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+/// Reference to an asset that may still be loading, cheap to clone,
+/// the asset is unloaded once every handle to it is dropped
+pub struct Handle<T> {
+    slot: Arc<Slot<T>>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            slot: self.slot.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.slot, &other.slot)
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Handle<T> {
+    /// File the asset comes from, with the asset root in front
+    pub fn path(&self) -> &Path {
+        &self.slot.path
+    }
+
+    pub fn state(&self) -> LoadState {
+        match &*self.slot.state.lock().unwrap() {
+            SlotState::Loading => LoadState::Loading,
+            SlotState::Loaded(_) => LoadState::Loaded,
+            SlotState::Failed(_) => LoadState::Failed,
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.state() == LoadState::Loaded
+    }
+
+    /// The asset, `None` while it's loading or if it failed to load
+    pub fn get(&self) -> Option<Arc<T>> {
+        match &*self.slot.state.lock().unwrap() {
+            SlotState::Loaded(asset) => Some(asset.clone()),
+            _ => None,
+        }
+    }
+
+    /// Why loading failed
+    pub fn error(&self) -> Option<Arc<RenderError>> {
+        match &*self.slot.state.lock().unwrap() {
+            SlotState::Failed(error) => Some(error.clone()),
+            _ => None,
+        }
+    }
+
+    /// Blocks until the asset is done loading
+    pub fn wait(&self) -> Result<Arc<T>, RenderError> {
+        let mut state = self.slot.state.lock().unwrap();
+        while let SlotState::Loading = *state {
+            state = self.slot.done.wait(state).unwrap();
+        }
+        match &*state {
+            SlotState::Loaded(asset) => Ok(asset.clone()),
+            SlotState::Failed(error) => Err(RenderError::Asset(self.path().into(), error.clone())),
+            SlotState::Loading => unreachable!(),
+        }
+    }
+}
+
+struct Shared {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    texture_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    root: RwLock<PathBuf>,
+    sampler: RwLock<Sampler>,
+    mipmaps: AtomicBool,
+    /// Slots that still have handles, by asset type and path
+    cache: Mutex<HashMap<(TypeId, PathBuf), Weak<dyn AnySlot>>>,
+    pending: AtomicUsize,
+    jobs: WorkerPool<Job>,
+    hot_reload: AtomicBool,
+    watcher: Once,
+}
+
+/// Loads files on a pool of background threads and hands out [`Handle`]s to them,
+/// loading the same file again gives out the asset that's already there
+///
+/// Cheap to clone, clones share the cache and the threads
+#[derive(Clone)]
+pub struct Assets {
+    shared: Arc<Shared>,
+}
+
+impl Assets {
+    pub(crate) fn new(
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+        texture_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    ) -> Self {
+        let jobs = WorkerPool::new("vge asset loader", MAX_WORKERS, None, |job: Job| job())
+            .expect("failed to spawn asset loader thread");
+
+        Self {
+            shared: Arc::new(Shared {
+                device,
+                queue,
+                texture_bind_group_layout,
+                root: RwLock::new(PathBuf::new()),
+                sampler: RwLock::new(Sampler::default()),
+                mipmaps: AtomicBool::new(false),
+                cache: Mutex::new(HashMap::new()),
+                pending: AtomicUsize::new(0),
+                jobs,
+                hot_reload: AtomicBool::new(false),
+                watcher: Once::new(),
+            }),
+        }
+    }
+
+    /// Directory paths given to [`Assets::load`] are relative to, the working directory by default
+    pub fn root(&self) -> PathBuf {
+        self.shared.root.read().unwrap().clone()
+    }
+
+    pub fn set_root(&self, root: impl Into<PathBuf>) {
+        *self.shared.root.write().unwrap() = root.into();
+    }
+
+    /// What loaded textures get sampled with
+    pub fn sampler(&self) -> Sampler {
+        *self.shared.sampler.read().unwrap()
+    }
+
+    /// Only affects textures loaded from now on, including reloads,
+    /// use [`Sprite::with_sampler`] to change a single one
+    pub fn set_sampler(&self, sampler: Sampler) {
+        *self.shared.sampler.write().unwrap() = sampler;
+    }
+
+    pub fn mipmaps(&self) -> bool {
+        self.shared.mipmaps.load(Ordering::Relaxed)
+    }
+
+    /// Generates a full mip chain for textures loaded from now on, including reloads
+    pub fn set_mipmaps(&self, enabled: bool) {
+        self.shared.mipmaps.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Starts loading `path` in the background, or hands out the asset
+    /// that's already loaded or loading from it
+    pub fn load<T: Asset>(&self, path: impl AsRef<Path>) -> Handle<T> {
+        let path = self.root().join(path);
+        let key = (TypeId::of::<T>(), path.clone());
+
+        let mut cache = self.shared.cache.lock().unwrap();
+        if let Some(slot) = cache.get(&key).and_then(Weak::upgrade)
+            && let Ok(slot) = slot.into_any().downcast::<Slot<T>>()
+        {
+            return Handle { slot };
+        }
+        // forget files nothing refers to anymore
+        cache.retain(|_, slot| slot.strong_count() > 0);
+
+        let slot = Arc::new(Slot {
+            path,
+            state: Mutex::new(SlotState::Loading),
+            done: Condvar::new(),
+        });
+        let erased: Arc<dyn AnySlot> = slot.clone();
+        cache.insert(key, Arc::downgrade(&erased));
+        drop(cache);
+
+        self.shared.pending.fetch_add(1, Ordering::Relaxed);
+        let shared = self.shared.clone();
+        let weak = Arc::downgrade(&slot);
+        let job: Job = Box::new(move || {
+            // skip the work if every handle got dropped in the meantime
+            if let Some(slot) = weak.upgrade() {
+                let state = match Self::read::<T>(&shared, &slot.path) {
+                    Ok(asset) => SlotState::Loaded(Arc::new(asset)),
+                    Err(error) => SlotState::Failed(Arc::new(error)),
+                };
+                slot.finish(state);
+            }
+            shared.pending.fetch_sub(1, Ordering::Relaxed);
+        });
+        if let Err(job) = self.shared.jobs.send(job) {
+            // no loader threads left, load on this one instead
+            job();
+        }
+
+        Handle { slot }
+    }
+
+    fn read<T: Asset>(shared: &Shared, path: &Path) -> Result<T, RenderError> {
+        let load = || {
+            let bytes = std::fs::read(path)?;
+            let ctx = LoadContext {
+                device: &shared.device,
+                queue: &shared.queue,
+                texture_bind_group_layout: &shared.texture_bind_group_layout,
+                path,
+                sampler: *shared.sampler.read().unwrap(),
+                mipmaps: shared.mipmaps.load(Ordering::Relaxed),
+            };
+            T::load(&ctx, &bytes)
+        };
+
+        // a panic would leave every handle waiting on a load that never finishes
+        std::panic::catch_unwind(AssertUnwindSafe(load)).unwrap_or_else(|panic| {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Err(RenderError::Panicked(message))
+        })
+    }
+
+    /// Loads files again when they change on disk, handles hand out the new
+    /// version from then on and keep the old one if it doesn't load
+    pub fn set_hot_reload(&self, enabled: bool) {
+        self.shared.hot_reload.store(enabled, Ordering::Relaxed);
+        if enabled {
+            let shared = Arc::downgrade(&self.shared);
+            self.shared.watcher.call_once(|| {
+                std::thread::Builder::new()
+                    .name("vge asset watcher".into())
+                    .spawn(move || Self::watch(shared))
+                    .expect("failed to spawn asset watcher thread");
+            });
+        }
+    }
+
+    pub fn hot_reload(&self) -> bool {
+        self.shared.hot_reload.load(Ordering::Relaxed)
+    }
+
+    fn watch(shared: Weak<Shared>) {
+        let mut watcher = FileWatcher::default();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            // stops along with the last `Assets`
+            let Some(shared) = shared.upgrade() else {
+                break;
+            };
+            if !shared.hot_reload.load(Ordering::Relaxed) {
+                continue;
+            }
+
+            let slots: HashMap<PathBuf, Vec<Arc<dyn AnySlot>>> = {
+                let cache = shared.cache.lock().unwrap();
+                let mut slots: HashMap<_, Vec<_>> = HashMap::new();
+                for slot in cache.values().filter_map(Weak::upgrade) {
+                    slots.entry(slot.path().to_owned()).or_default().push(slot);
+                }
+                slots
+            };
+
+            watcher.retain(|path| slots.contains_key(path));
+            for path in slots.keys() {
+                watcher.watch(path);
+            }
+            for path in watcher.changed() {
+                for slot in &slots[&path] {
+                    slot.reload(&shared);
+                }
+            }
+        }
+    }
+
+    /// Assets still waiting to finish loading
+    pub fn pending(&self) -> usize {
+        self.shared.pending.load(Ordering::Relaxed)
+    }
+
+    /// Whether everything started so far is done loading
+    pub fn is_idle(&self) -> bool {
+        self.pending() == 0
+    }
+
+    /// Files that are loaded or loading and still have handles
+    pub fn len(&self) -> usize {
+        let cache = self.shared.cache.lock().unwrap();
+        cache
+            .values()
+            .filter(|slot| slot.strong_count() > 0)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,438 @@
+use std::{collections::HashMap, sync::Arc};
+
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use vge_math::{Rect, Vec2};
+
+use crate::{
+    Gfx, RenderError,
+    mesh::{Sprite, TexturedQuad},
+};
+
+/// Largest page [`AtlasBuilder`] makes unless told otherwise, supported by every backend
+const DEFAULT_PAGE_SIZE: u32 = 2048;
+
+/// Row of rectangles in a [`ShelfPacker`], as tall as the rectangle that started it
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+}
+
+/// Packs rectangles into rows from the top down, works best when
+/// the rectangles come in sorted from tallest to shortest
+pub(crate) struct ShelfPacker {
+    size: (u32, u32),
+    /// Gap kept between rectangles and along the edges
+    padding: u32,
+    shelves: Vec<Shelf>,
+    /// Right and bottom edge of everything packed so far
+    used: (u32, u32),
+}
+
+impl ShelfPacker {
+    pub(crate) fn new(size: (u32, u32), padding: u32) -> Self {
+        Self {
+            size,
+            padding,
+            shelves: Vec::new(),
+            used: (0, 0),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.shelves.clear();
+        self.used = (0, 0);
+    }
+
+    pub(crate) fn used(&self) -> (u32, u32) {
+        self.used
+    }
+
+    /// Top left corner of the room found for a `width` by `height` rectangle
+    pub(crate) fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let padded = (width + self.padding, height + self.padding);
+        let position = self.find(padded)?;
+        self.used = (
+            self.used.0.max(position.0 + width),
+            self.used.1.max(position.1 + height),
+        );
+        Some(position)
+    }
+
+    fn find(&mut self, padded: (u32, u32)) -> Option<(u32, u32)> {
+        let width = self.size.0;
+        if let Some(shelf) = self
+            .shelves
+            .iter_mut()
+            .find(|shelf| padded.1 <= shelf.height && shelf.x + padded.0 <= width)
+        {
+            let position = (shelf.x, shelf.y);
+            shelf.x += padded.0;
+            return Some(position);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map_or(self.padding, |shelf| shelf.y + shelf.height);
+        if y + padded.1 > self.size.1 || self.padding + padded.0 > width {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y,
+            height: padded.1,
+            x: self.padding + padded.0,
+        });
+        Some((self.padding, y))
+    }
+}
+
+/// Image added to an [`AtlasBuilder`], look its sprite up with [`TextureAtlas::sprite`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AtlasRegion(usize);
+
+/// Packs many images into as few textures as possible so sprites using
+/// them can be drawn together, instead of one texture per image
+pub struct AtlasBuilder {
+    page_size: u32,
+    padding: u32,
+    extrude: u32,
+    images: Vec<(Option<String>, DynamicImage)>,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            padding: 1,
+            extrude: 1,
+            images: Vec::new(),
+        }
+    }
+}
+
+impl AtlasBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Largest width and height of a page, pages that end up emptier get shrunk,
+    /// capped at the largest texture the device supports
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Transparent texels left between images
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Texels the edges of every image get repeated outwards by,
+    /// keeps filtering at the edges from bleeding in the padding
+    pub fn extrude(mut self, extrude: u32) -> Self {
+        self.extrude = extrude;
+        self
+    }
+
+    pub fn add(&mut self, image: DynamicImage) -> AtlasRegion {
+        self.images.push((None, image));
+        AtlasRegion(self.images.len() - 1)
+    }
+
+    /// Same as [`AtlasBuilder::add`], the sprite can also be looked up by `name`
+    pub fn add_named(&mut self, name: impl Into<String>, image: DynamicImage) -> AtlasRegion {
+        self.images.push((Some(name.into()), image));
+        AtlasRegion(self.images.len() - 1)
+    }
+
+    pub fn add_file(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<AtlasRegion, RenderError> {
+        let path = path.as_ref();
+        let image = image::open(path)?;
+        Ok(self.add_named(path.to_string_lossy(), image))
+    }
+
+    /// Pages are sampled with [`Gfx::default_sampler`]
+    pub fn build(self, gfx: &Gfx) -> Result<TextureAtlas, RenderError> {
+        let border = self.extrude * 2;
+
+        // tallest first keeps the shelves from wasting room
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by_key(|i| {
+            let (width, height) = self.images[*i].1.dimensions();
+            std::cmp::Reverse((height, width))
+        });
+
+        let page_size = self
+            .page_size
+            .min(gfx.device.limits().max_texture_dimension_2d);
+        let page_size = (page_size, page_size);
+        let mut packers: Vec<ShelfPacker> = Vec::new();
+        let mut placements = vec![(0, 0, 0); self.images.len()];
+        for i in order {
+            let (width, height) = self.images[i].1.dimensions();
+            let (width, height) = (width + border, height + border);
+
+            let placed = packers
+                .iter_mut()
+                .enumerate()
+                .find_map(|(page, packer)| Some((page, packer.allocate(width, height)?)));
+            let (page, (x, y)) = match placed {
+                Some(placed) => placed,
+                None => {
+                    let mut packer = ShelfPacker::new(page_size, self.padding);
+                    let position = packer.allocate(width, height).ok_or_else(|| {
+                        let name = self.images[i].0.clone();
+                        RenderError::AtlasTooSmall(name.unwrap_or_else(|| format!("#{i}")))
+                    })?;
+                    packers.push(packer);
+                    (packers.len() - 1, position)
+                }
+            };
+            placements[i] = (page, x + self.extrude, y + self.extrude);
+        }
+
+        let mut pages: Vec<RgbaImage> = packers
+            .iter()
+            .map(|packer| {
+                // only as big as it needs to be, kept to powers of two
+                let (width, height) = packer.used();
+                let width = (width + self.padding).next_power_of_two().min(page_size.0);
+                let height = (height + self.padding).next_power_of_two().min(page_size.1);
+                RgbaImage::new(width, height)
+            })
+            .collect();
+
+        for ((_, image), (page, x, y)) in self.images.iter().zip(&placements) {
+            blit_extruded(&mut pages[*page], &image.to_rgba8(), *x, *y, self.extrude);
+        }
+
+        let textures: Vec<Arc<TexturedQuad>> = pages
+            .into_iter()
+            .map(|page| {
+                TexturedQuad::from_image_sampled(
+                    &gfx.device,
+                    &gfx.queue,
+                    &gfx.texture_bind_group_layout,
+                    &DynamicImage::ImageRgba8(page),
+                    gfx.default_sampler(),
+                    Some("Atlas page"),
+                )
+                .map(Arc::new)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let regions = self
+            .images
+            .iter()
+            .zip(placements)
+            .map(|((_, image), (page, x, y))| {
+                let min = Vec2::new(x as f32, y as f32);
+                let (width, height) = image.dimensions();
+                (
+                    page,
+                    Rect::new(min, min + Vec2::new(width as f32, height as f32)),
+                )
+            })
+            .collect();
+
+        let names = self
+            .images
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (name, _))| Some((name?, AtlasRegion(i))))
+            .collect();
+
+        Ok(TextureAtlas {
+            pages: textures,
+            regions,
+            names,
+        })
+    }
+}
+
+/// Copies `image` into `page` at `x`, `y` and repeats its edges `extrude` texels outwards
+fn blit_extruded(page: &mut RgbaImage, image: &RgbaImage, x: u32, y: u32, extrude: u32) {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let extrude = extrude as i64;
+    for dy in -extrude..height as i64 + extrude {
+        for dx in -extrude..width as i64 + extrude {
+            let source = (
+                dx.clamp(0, width as i64 - 1) as u32,
+                dy.clamp(0, height as i64 - 1) as u32,
+            );
+            let target = ((x as i64 + dx) as u32, (y as i64 + dy) as u32);
+            page.put_pixel(target.0, target.1, *image.get_pixel(source.0, source.1));
+        }
+    }
+}
+
+/// Images packed together by an [`AtlasBuilder`]
+pub struct TextureAtlas {
+    pages: Vec<Arc<TexturedQuad>>,
+    /// Page and texel rect of every region, in the order they were added
+    regions: Vec<(usize, Rect)>,
+    names: HashMap<String, AtlasRegion>,
+}
+
+impl TextureAtlas {
+    pub fn builder() -> AtlasBuilder {
+        AtlasBuilder::new()
+    }
+
+    /// Sprite drawing just the one image, sprites on the same page batch together
+    pub fn sprite(&self, region: AtlasRegion) -> Sprite {
+        let (page, rect) = self.regions[region.0];
+        let texture = &self.pages[page];
+        let (width, height) = texture.size();
+        let size = Vec2::new(width as f32, height as f32);
+        Sprite::with_region(texture.clone(), Rect::new(rect.min / size, rect.max / size))
+    }
+
+    /// Sprite of an image added with [`AtlasBuilder::add_named`] or [`AtlasBuilder::add_file`]
+    pub fn get(&self, name: &str) -> Option<Sprite> {
+        self.names.get(name).map(|region| self.sprite(*region))
+    }
+
+    /// Where the region ended up, its page and the texels it covers
+    pub fn region(&self, region: AtlasRegion) -> (usize, Rect) {
+        self.regions[region.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn packs_without_overlap() {
+        let padding = 2;
+        let mut packer = ShelfPacker::new((64, 64), padding);
+        let sizes = [
+            (12, 14),
+            (20, 12),
+            (9, 12),
+            (30, 10),
+            (7, 9),
+            (15, 8),
+            (5, 5),
+            (3, 2),
+        ];
+
+        let mut placed: Vec<(u32, u32, u32, u32)> = Vec::new();
+        for _ in 0..3 {
+            for (width, height) in sizes {
+                if let Some((x, y)) = packer.allocate(width, height) {
+                    placed.push((x, y, width, height));
+                }
+            }
+        }
+        assert!(placed.len() > sizes.len());
+
+        for (i, &(x, y, width, height)) in placed.iter().enumerate() {
+            assert!(x >= padding && y >= padding);
+            assert!(x + width + padding <= 64 && y + height + padding <= 64);
+            for &(other_x, other_y, other_width, other_height) in &placed[i + 1..] {
+                let apart = x + width + padding <= other_x
+                    || other_x + other_width + padding <= x
+                    || y + height + padding <= other_y
+                    || other_y + other_height + padding <= y;
+                assert!(
+                    apart,
+                    "{:?} and {:?} overlap",
+                    placed[i],
+                    (other_x, other_y)
+                );
+            }
+        }
+
+        let used = placed.iter().fold((0, 0), |used, &(x, y, width, height)| {
+            (used.0.max(x + width), used.1.max(y + height))
+        });
+        assert_eq!(packer.used(), used);
+    }
+
+    #[test]
+    fn allocate_fails_when_full() {
+        let mut packer = ShelfPacker::new((17, 17), 1);
+        assert_eq!(packer.allocate(7, 7), Some((1, 1)));
+        assert_eq!(packer.allocate(7, 7), Some((9, 1)));
+        assert_eq!(packer.allocate(7, 7), Some((1, 9)));
+        assert_eq!(packer.allocate(7, 7), Some((9, 9)));
+        assert_eq!(packer.allocate(7, 7), None);
+        assert_eq!(packer.allocate(1, 1), None);
+
+        // the padding along the edges counts too
+        packer.clear();
+        assert_eq!(packer.used(), (0, 0));
+        assert_eq!(packer.allocate(16, 16), None);
+        assert_eq!(packer.allocate(15, 15), Some((1, 1)));
+    }
+
+    #[test]
+    fn build_opens_new_pages() {
+        let gfx = crate::headless((1, 1)).unwrap();
+        let mut builder = AtlasBuilder::new().page_size(17).padding(1).extrude(0);
+        let regions: Vec<_> = (0..5)
+            .map(|_| builder.add(DynamicImage::new_rgba8(7, 7)))
+            .collect();
+        let atlas = builder.build(&gfx).unwrap();
+
+        assert_eq!(atlas.page_count(), 2);
+        assert_eq!(atlas.region(regions[4]).0, 1);
+
+        let mut builder = AtlasBuilder::new().page_size(16);
+        builder.add_named("big", DynamicImage::new_rgba8(16, 16));
+        assert!(matches!(
+            builder.build(&gfx),
+            Err(RenderError::AtlasTooSmall(name)) if name == "big"
+        ));
+    }
+
+    #[test]
+    fn extrudes_edges() {
+        let colors = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [9, 9, 9, 255],
+        ];
+        let image = RgbaImage::from_fn(2, 2, |x, y| Rgba(colors[(y * 2 + x) as usize]));
+        let mut page = RgbaImage::new(7, 7);
+        blit_extruded(&mut page, &image, 2, 2, 1);
+
+        let expected = |x: u32, y: u32| {
+            if !(1..5).contains(&x) || !(1..5).contains(&y) {
+                return [0; 4];
+            }
+            // every texel of the border repeats the closest one of the image
+            let (x, y) = ((x.max(2) - 2).min(1), (y.max(2) - 2).min(1));
+            colors[(y * 2 + x) as usize]
+        };
+        for (x, y, pixel) in page.enumerate_pixels() {
+            assert_eq!(pixel.0, expected(x, y), "texel {x}, {y}");
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use std::{ops::Range, sync::Arc};
+
+use vge_math::{Transform2D, Vec2};
+
+use crate::{
+    material::{Material, ShaderKind, material_id},
+    mesh::{DrawSprite, TexturedQuad},
+    paint::{Painter, Run},
+    primitives::{VertexColored, VertexTextured},
+};
+
+/// Sprites the buffers have room for before they first need to grow
+const INITIAL_CAPACITY: usize = 1024;
+
+/// Run of sprites sharing a texture, material and layer that can go out in a single draw call
+pub(crate) struct Batch {
+    pub(crate) texture: Arc<TexturedQuad>,
+    pub(crate) material: Option<Material>,
+    pub(crate) layer: i32,
+    pub(crate) indices: Range<u32>,
+}
+
+/// Collects every sprite of a frame into one vertex buffer,
+/// sorted so sprites sharing a texture end up next to each other
+pub(crate) struct SpriteBatch {
+    vertices: Vec<VertexTextured>,
+    batches: Vec<Batch>,
+    vtx_buf: wgpu::Buffer,
+    idx_buf: wgpu::Buffer,
+    /// In sprites
+    capacity: usize,
+}
+
+impl SpriteBatch {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        Self {
+            vertices: Vec::new(),
+            batches: Vec::new(),
+            vtx_buf: Self::create_vertex_buffer(device, INITIAL_CAPACITY),
+            idx_buf: Self::create_index_buffer(device, INITIAL_CAPACITY),
+            capacity: INITIAL_CAPACITY,
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sprite vertex buffer"),
+            size: (capacity * 4 * std::mem::size_of::<VertexTextured>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_index_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        // every sprite is a quad, so the indices never change
+        let indices: Vec<u32> = (0..capacity as u32)
+            .flat_map(|i| [0, 1, 2, 2, 1, 3].map(|idx| i * 4 + idx))
+            .collect();
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sprite index buffer"),
+            size: (indices.len() * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDEX,
+            mapped_at_creation: true,
+        });
+        buffer
+            .slice(..)
+            .get_mapped_range_mut()
+            .copy_from_slice(bytemuck::cast_slice(&indices));
+        buffer.unmap();
+        buffer
+    }
+
+    /// Builds the vertices for `sprites` and uploads them
+    pub(crate) fn prepare<'a>(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        sprites: impl IntoIterator<Item = &'a DrawSprite>,
+    ) {
+        self.vertices.clear();
+        self.batches.clear();
+
+        let material = |spr: &'a DrawSprite| {
+            spr.material
+                .as_ref()
+                .filter(|material| material.shader().kind() == ShaderKind::Sprite)
+        };
+        let mut order: Vec<&DrawSprite> = sprites.into_iter().collect();
+        order.sort_by_key(|spr| (spr.layer, material_id(material(spr)), spr.sprite.texture.id));
+
+        let count = order.len();
+        for spr in order {
+            let start = (self.vertices.len() / 4 * 6) as u32;
+            self.vertices.extend(Self::vertices(spr));
+
+            let material = material(spr);
+            match self.batches.last_mut() {
+                Some(batch)
+                    if batch.texture.id == spr.sprite.texture.id
+                        && material_id(batch.material.as_ref()) == material_id(material)
+                        && batch.layer == spr.layer =>
+                {
+                    batch.indices.end += 6;
+                }
+                _ => self.batches.push(Batch {
+                    texture: spr.sprite.texture.clone(),
+                    material: material.cloned(),
+                    layer: spr.layer,
+                    indices: start..start + 6,
+                }),
+            }
+        }
+
+        if count > self.capacity {
+            self.capacity = count.next_power_of_two();
+            self.vtx_buf = Self::create_vertex_buffer(device, self.capacity);
+            self.idx_buf = Self::create_index_buffer(device, self.capacity);
+        }
+
+        queue.write_buffer(&self.vtx_buf, 0, bytemuck::cast_slice(&self.vertices));
+    }
+
+    fn vertices(spr: &DrawSprite) -> [VertexTextured; 4] {
+        let uv = spr.sprite.texture_uv(spr.uv);
+
+        // one world unit per texel at scale 1
+        let (width, height) = spr.sprite.texture.size();
+        let size = Vec2::new(
+            width as f32 * (uv.max.x - uv.min.x) * spr.scale.x,
+            height as f32 * (uv.max.y - uv.min.y) * spr.scale.y,
+        );
+        let transform = Transform2D::new(spr.position, spr.rotation, size);
+
+        // same winding as primitives::Quad, bottom left, bottom right, top left, top right
+        let corners = [
+            (Vec2::new(0.0, 0.0), Vec2::new(uv.min.x, uv.max.y)),
+            (Vec2::new(1.0, 0.0), Vec2::new(uv.max.x, uv.max.y)),
+            (Vec2::new(0.0, 1.0), Vec2::new(uv.min.x, uv.min.y)),
+            (Vec2::new(1.0, 1.0), Vec2::new(uv.max.x, uv.min.y)),
+        ];
+
+        corners.map(|(corner, tex_coords)| {
+            let position = transform.transform_point(corner - spr.origin);
+            VertexTextured::tinted(position.extend(0.0), tex_coords, spr.tint)
+        })
+    }
+
+    pub(crate) fn batches(&self) -> &[Batch] {
+        &self.batches
+    }
+
+    pub(crate) fn bind(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_vertex_buffer(0, self.vtx_buf.slice(..));
+        render_pass.set_index_buffer(self.idx_buf.slice(..), wgpu::IndexFormat::Uint32);
+    }
+}
+
+/// Uploads the shapes of a [`Painter`] with their index ranges regrouped by layer
+pub(crate) struct ShapeBatch {
+    indices: Vec<u32>,
+    runs: Vec<Run>,
+    vtx_buf: wgpu::Buffer,
+    idx_buf: wgpu::Buffer,
+    /// In vertices and indices
+    capacity: (usize, usize),
+}
+
+impl ShapeBatch {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let capacity = (INITIAL_CAPACITY * 4, INITIAL_CAPACITY * 6);
+        Self {
+            indices: Vec::new(),
+            runs: Vec::new(),
+            vtx_buf: Self::create_vertex_buffer(device, capacity.0),
+            idx_buf: Self::create_index_buffer(device, capacity.1),
+            capacity,
+        }
+    }
+
+    fn create_vertex_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shape vertex buffer"),
+            size: (capacity * std::mem::size_of::<VertexColored>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_index_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shape index buffer"),
+            size: (capacity * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        painter: &Painter,
+    ) {
+        self.indices.clear();
+        self.runs.clear();
+
+        // stable, so shapes on the same layer keep the order they were painted in
+        let mut order: Vec<&Run> = painter.runs().iter().collect();
+        order.sort_by_key(|run| run.layer);
+
+        for run in order {
+            let start = self.indices.len() as u32;
+            let range = run.indices.start as usize..run.indices.end as usize;
+            self.indices.extend_from_slice(&painter.indices()[range]);
+            let end = self.indices.len() as u32;
+
+            let material = run
+                .material
+                .as_ref()
+                .filter(|material| material.shader().kind() == ShaderKind::Shape);
+            match self.runs.last_mut() {
+                Some(last)
+                    if last.layer == run.layer
+                        && material_id(last.material.as_ref()) == material_id(material) =>
+                {
+                    last.indices.end = end
+                }
+                _ => self.runs.push(Run {
+                    layer: run.layer,
+                    material: material.cloned(),
+                    indices: start..end,
+                }),
+            }
+        }
+
+        let vertices = painter.vertices();
+        if vertices.len() > self.capacity.0 {
+            self.capacity.0 = vertices.len().next_power_of_two();
+            self.vtx_buf = Self::create_vertex_buffer(device, self.capacity.0);
+        }
+        if self.indices.len() > self.capacity.1 {
+            self.capacity.1 = self.indices.len().next_power_of_two();
+            self.idx_buf = Self::create_index_buffer(device, self.capacity.1);
+        }
+
+        if !vertices.is_empty() {
+            queue.write_buffer(&self.vtx_buf, 0, bytemuck::cast_slice(vertices));
+            queue.write_buffer(&self.idx_buf, 0, bytemuck::cast_slice(&self.indices));
+        }
+    }
+
+    pub(crate) fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    pub(crate) fn bind(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_vertex_buffer(0, self.vtx_buf.slice(..));
+        render_pass.set_index_buffer(self.idx_buf.slice(..), wgpu::IndexFormat::Uint32);
+    }
+}
+
+/// Single draw call of a frame
+pub(crate) enum DrawCall<'a> {
+    Sprites(&'a Batch),
+    Shapes(&'a Run),
+}
+
+/// Interleaves sprites and shapes by layer, sprites go first within a layer
+pub(crate) fn draw_order<'a>(
+    sprites: &'a SpriteBatch,
+    shapes: &'a ShapeBatch,
+) -> Vec<DrawCall<'a>> {
+    let mut sprites = sprites.batches().iter().peekable();
+    let mut shapes = shapes.runs().iter().peekable();
+    let mut calls = Vec::new();
+
+    loop {
+        let call = match (sprites.peek(), shapes.peek()) {
+            (Some(batch), Some(run)) if batch.layer <= run.layer => {
+                sprites.next().map(DrawCall::Sprites)
+            }
+            (_, Some(_)) => shapes.next().map(DrawCall::Shapes),
+            (Some(_), None) => sprites.next().map(DrawCall::Sprites),
+            (None, None) => None,
+        };
+
+        match call {
+            Some(call) => calls.push(call),
+            None => break calls,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Gfx, Shader, mesh::Sprite};
+
+    fn sprite(gfx: &Gfx) -> Sprite {
+        let image = image::DynamicImage::new_rgba8(1, 1);
+        let texture = TexturedQuad::from_image(
+            &gfx.device,
+            &gfx.queue,
+            &gfx.texture_bind_group_layout,
+            &image,
+            None,
+        )
+        .unwrap();
+        Sprite::from_texture(Arc::new(texture))
+    }
+
+    fn batches(batch: &SpriteBatch) -> Vec<(u64, u64, i32, Range<u32>)> {
+        batch
+            .batches()
+            .iter()
+            .map(|batch| {
+                let material = material_id(batch.material.as_ref());
+                (
+                    batch.texture.id,
+                    material,
+                    batch.layer,
+                    batch.indices.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sorts_into_runs() {
+        let gfx = crate::headless((1, 1)).unwrap();
+        let (a, b) = (sprite(&gfx), sprite(&gfx));
+        let (a_id, b_id) = (a.texture.id, b.texture.id);
+        let shader = Shader::new(
+            &gfx,
+            ShaderKind::Sprite,
+            include_str!("../../../assets/shaders/textured.wgsl"),
+            &[],
+        )
+        .unwrap();
+        let material = Material::new(&gfx, &shader).unwrap();
+        let material_id = material_id(Some(&material));
+
+        let sprites = [
+            b.at(Vec2::ZERO),
+            a.at(Vec2::ZERO).layer(1),
+            a.at(Vec2::ZERO),
+            b.at(Vec2::ZERO).material(material),
+            b.at(Vec2::ZERO),
+            a.at(Vec2::ZERO).layer(-1),
+            a.at(Vec2::ZERO),
+        ];
+        let mut batch = SpriteBatch::new(&gfx.device);
+        batch.prepare(&gfx.device, &gfx.queue, &sprites);
+
+        assert_eq!(
+            batches(&batch),
+            [
+                (a_id, 0, -1, 0..6),
+                (a_id, 0, 0, 6..18),
+                (b_id, 0, 0, 18..30),
+                (b_id, material_id, 0, 30..36),
+                (a_id, 0, 1, 36..42),
+            ]
+        );
+        assert_eq!(batch.vertices.len(), sprites.len() * 4);
+    }
+
+    #[test]
+    fn grows_to_powers_of_two() {
+        let gfx = crate::headless((1, 1)).unwrap();
+        let sprite = sprite(&gfx);
+        let mut batch = SpriteBatch::new(&gfx.device);
+
+        let sprites = vec![sprite.at(Vec2::ZERO); INITIAL_CAPACITY];
+        batch.prepare(&gfx.device, &gfx.queue, &sprites);
+        assert_eq!(batch.capacity, INITIAL_CAPACITY);
+
+        let sprites = vec![sprite.at(Vec2::ZERO); INITIAL_CAPACITY + 1];
+        batch.prepare(&gfx.device, &gfx.queue, &sprites);
+        assert_eq!(batch.capacity, INITIAL_CAPACITY * 2);
+        let size = (batch.capacity * 4 * std::mem::size_of::<VertexTextured>()) as u64;
+        assert_eq!(batch.vtx_buf.size(), size);
+        assert_eq!(batch.idx_buf.size(), (batch.capacity * 6 * 4) as u64);
+        assert_eq!(
+            batches(&batch),
+            [(
+                sprite.texture.id,
+                0,
+                0,
+                0..(INITIAL_CAPACITY as u32 + 1) * 6
+            )]
+        );
+
+        // never shrinks back down
+        batch.prepare(&gfx.device, &gfx.queue, &sprites[..1]);
+        assert_eq!(batch.capacity, INITIAL_CAPACITY * 2);
+    }
+
+    #[test]
+    fn interleaves_by_layer() {
+        let gfx = crate::headless((1, 1)).unwrap();
+        let sprite = sprite(&gfx);
+        let sprites = [
+            sprite.at(Vec2::ZERO).layer(2),
+            sprite.at(Vec2::ZERO),
+            sprite.at(Vec2::ZERO).layer(-1),
+        ];
+        let mut painter = Painter::new();
+        let rect = vge_math::Rect::new(Vec2::ZERO, Vec2::ONE);
+        painter.layer(1).rect(rect, crate::Color::WHITE);
+        painter.layer(0).rect(rect, crate::Color::WHITE);
+        painter.layer(3).rect(rect, crate::Color::WHITE);
+        painter.layer(0).rect(rect, crate::Color::WHITE);
+
+        let mut sprite_batch = SpriteBatch::new(&gfx.device);
+        sprite_batch.prepare(&gfx.device, &gfx.queue, &sprites);
+        let mut shape_batch = ShapeBatch::new(&gfx.device);
+        shape_batch.prepare(&gfx.device, &gfx.queue, &painter);
+
+        // both shapes on layer 0 end up in one run, in the order they were painted
+        let runs: Vec<_> = shape_batch
+            .runs()
+            .iter()
+            .map(|run| (run.layer, run.indices.clone()))
+            .collect();
+        assert_eq!(runs, [(0, 0..12), (1, 12..18), (3, 18..24)]);
+
+        let calls: Vec<_> = draw_order(&sprite_batch, &shape_batch)
+            .into_iter()
+            .map(|call| match call {
+                DrawCall::Sprites(batch) => ("sprites", batch.layer),
+                DrawCall::Shapes(run) => ("shapes", run.layer),
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                ("sprites", -1),
+                ("sprites", 0),
+                ("shapes", 0),
+                ("shapes", 1),
+                ("sprites", 2),
+                ("shapes", 3),
+            ]
+        );
+    }
+}
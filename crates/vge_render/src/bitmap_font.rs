@@ -0,0 +1,350 @@
+use std::{collections::HashMap, sync::Arc};
+
+use vge_math::{Rect, Vec2};
+
+use crate::{RenderError, mesh::TexturedQuad, reader::Reader};
+
+/// Glyph stored in one of the pages of a [`BitmapFont`]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BitmapGlyph {
+    pub(crate) page: usize,
+    /// Texels of the glyph in its page, (0, 0) is the top left
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    /// From the pen position to the top left corner of the glyph, y pointing down
+    pub(crate) offset: Vec2,
+    pub(crate) advance: f32,
+}
+
+/// Pre-rendered glyphs on one or more textures, drawn texel for texel
+pub(crate) struct BitmapFont {
+    pub(crate) pages: Vec<Arc<TexturedQuad>>,
+    /// Distance from one baseline to the next
+    pub(crate) line_height: f32,
+    /// Distance from the top of a line to the baseline
+    pub(crate) base: f32,
+    pub(crate) glyphs: HashMap<u32, BitmapGlyph>,
+    pub(crate) kerning: HashMap<(u32, u32), f32>,
+}
+
+impl BitmapFont {
+    /// Part of the page texture `glyph` covers, in normalized coordinates
+    pub(crate) fn uv(&self, glyph: &BitmapGlyph) -> Rect {
+        let (width, height) = self.pages[glyph.page].size();
+        let size = Vec2::new(width as f32, height as f32);
+        let min = Vec2::new(glyph.x as f32, glyph.y as f32);
+        let max = min + Vec2::new(glyph.width as f32, glyph.height as f32);
+        Rect::new(min / size, max / size)
+    }
+}
+
+/// Contents of an AngelCode BMFont `.fnt` file, before the pages are loaded
+#[derive(Debug, Default)]
+pub(crate) struct BmFontDesc {
+    pub(crate) line_height: f32,
+    pub(crate) base: f32,
+    /// File names of the page images, relative to the `.fnt` file
+    pub(crate) pages: Vec<String>,
+    pub(crate) glyphs: HashMap<u32, BitmapGlyph>,
+    pub(crate) kerning: HashMap<(u32, u32), f32>,
+}
+
+impl BmFontDesc {
+    /// Reads either the text or the binary format
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Self, RenderError> {
+        if bytes.starts_with(b"BMF") {
+            Self::parse_binary(bytes)
+        } else {
+            let text = std::str::from_utf8(bytes)
+                .map_err(|_| RenderError::BitmapFont("not valid utf-8 or BMF".into()))?;
+            Self::parse_text(text)
+        }
+    }
+
+    fn parse_text(text: &str) -> Result<Self, RenderError> {
+        let mut desc = Self::default();
+
+        for line in text.lines() {
+            let mut tokens = tokenize(line);
+            let Some(tag) = tokens.next() else {
+                continue;
+            };
+            let values: HashMap<&str, &str> = tokens
+                .filter_map(|token| token.split_once('='))
+                .map(|(key, value)| (key, value.trim_matches('"')))
+                .collect();
+            let int = |key: &str| -> Result<i64, RenderError> {
+                values
+                    .get(key)
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(|| RenderError::BitmapFont(format!("{tag} is missing {key}")))
+            };
+
+            match tag {
+                "common" => {
+                    desc.line_height = int("lineHeight")? as f32;
+                    desc.base = int("base")? as f32;
+                }
+                "page" => {
+                    // glyphs store their page in a byte in the binary format
+                    let id = int("id")?;
+                    let id = usize::try_from(id)
+                        .ok()
+                        .filter(|id| *id <= u8::MAX as usize)
+                        .ok_or_else(|| RenderError::BitmapFont(format!("invalid page id {id}")))?;
+                    let file = values.get("file").copied().unwrap_or_default();
+                    if desc.pages.len() <= id {
+                        desc.pages.resize(id + 1, String::new());
+                    }
+                    desc.pages[id] = file.to_owned();
+                }
+                "char" => {
+                    let glyph = BitmapGlyph {
+                        page: int("page")? as usize,
+                        x: int("x")? as u32,
+                        y: int("y")? as u32,
+                        width: int("width")? as u32,
+                        height: int("height")? as u32,
+                        offset: Vec2::new(int("xoffset")? as f32, int("yoffset")? as f32),
+                        advance: int("xadvance")? as f32,
+                    };
+                    // -1 is the glyph for missing characters
+                    desc.glyphs.insert(int("id")? as u32, glyph);
+                }
+                "kerning" => {
+                    let pair = (int("first")? as u32, int("second")? as u32);
+                    desc.kerning.insert(pair, int("amount")? as f32);
+                }
+                _ => {}
+            }
+        }
+
+        desc.validate()
+    }
+
+    fn parse_binary(bytes: &[u8]) -> Result<Self, RenderError> {
+        let mut reader = Reader::new(bytes, RenderError::BitmapFont);
+        reader.skip(3)?;
+        let version = reader.u8()?;
+        if version != 3 {
+            return Err(RenderError::BitmapFont(format!(
+                "unsupported binary version {version}"
+            )));
+        }
+
+        let mut desc = Self::default();
+        while !reader.is_empty() {
+            let kind = reader.u8()?;
+            let size = reader.u32()? as usize;
+            let mut block = reader.sub(size)?;
+
+            match kind {
+                2 => {
+                    desc.line_height = block.u16()? as f32;
+                    desc.base = block.u16()? as f32;
+                }
+                3 => {
+                    for name in block.rest().split(|byte| *byte == 0) {
+                        if !name.is_empty() {
+                            desc.pages.push(String::from_utf8_lossy(name).into_owned());
+                        }
+                    }
+                }
+                4 => {
+                    while !block.is_empty() {
+                        let id = block.u32()?;
+                        let (x, y) = (block.u16()? as u32, block.u16()? as u32);
+                        let (width, height) = (block.u16()? as u32, block.u16()? as u32);
+                        let offset = Vec2::new(block.i16()? as f32, block.i16()? as f32);
+                        let advance = block.i16()? as f32;
+                        let page = block.u8()? as usize;
+                        block.u8()?;
+
+                        desc.glyphs.insert(
+                            id,
+                            BitmapGlyph {
+                                page,
+                                x,
+                                y,
+                                width,
+                                height,
+                                offset,
+                                advance,
+                            },
+                        );
+                    }
+                }
+                5 => {
+                    while !block.is_empty() {
+                        let pair = (block.u32()?, block.u32()?);
+                        desc.kerning.insert(pair, block.i16()? as f32);
+                    }
+                }
+                // info block, nothing in there matters for drawing
+                _ => {}
+            }
+        }
+
+        desc.validate()
+    }
+
+    fn validate(self) -> Result<Self, RenderError> {
+        if self.line_height <= 0.0 {
+            return Err(RenderError::BitmapFont("missing common block".into()));
+        }
+        if let Some(glyph) = self.glyphs.values().find(|g| g.page >= self.pages.len()) {
+            return Err(RenderError::BitmapFont(format!(
+                "glyph on page {} but there are only {} pages",
+                glyph.page,
+                self.pages.len()
+            )));
+        }
+        Ok(self)
+    }
+}
+
+/// Splits a line of a text `.fnt` on spaces, except for the ones in quotes
+fn tokenize(line: &str) -> impl Iterator<Item = &str> {
+    let mut rest = line.trim();
+    std::iter::from_fn(move || {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+
+        let mut quoted = false;
+        let end = rest
+            .char_indices()
+            .find(|(_, ch)| {
+                if *ch == '"' {
+                    quoted = !quoted;
+                }
+                ch.is_whitespace() && !quoted
+            })
+            .map_or(rest.len(), |(i, _)| i);
+
+        let (token, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(token)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"info face="Pixel Font" size=8 bold=0 italic=0 charset="" spacing=1,1
+common lineHeight=10 base=8 scaleW=64 scaleH=64 pages=2 packed=0
+page id=0 file="font 0.png"
+page id=1 file="font_1.png"
+chars count=2
+char id=65   x=1     y=2     width=5     height=7     xoffset=0     yoffset=1     xadvance=6     page=0  chnl=15
+char id=-1   x=10    y=0     width=4     height=4     xoffset=-1    yoffset=2     xadvance=5     page=1  chnl=15
+kernings count=1
+kerning first=65  second=86  amount=-1
+"#;
+
+    fn block(kind: u8, contents: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![kind];
+        bytes.extend((contents.len() as u32).to_le_bytes());
+        bytes.extend(contents);
+        bytes
+    }
+
+    fn binary(glyph_page: u8) -> Vec<u8> {
+        let mut bytes = b"BMF\x03".to_vec();
+        bytes.extend(block(1, &[0; 14]));
+
+        let mut common = Vec::new();
+        common.extend(12u16.to_le_bytes());
+        common.extend(9u16.to_le_bytes());
+        common.extend([0; 11]);
+        bytes.extend(block(2, &common));
+
+        bytes.extend(block(3, b"a.png\0b.png\0"));
+
+        let mut chars = Vec::new();
+        chars.extend(66u32.to_le_bytes());
+        for value in [3u16, 4, 5, 6] {
+            chars.extend(value.to_le_bytes());
+        }
+        for value in [-1i16, 2, 7] {
+            chars.extend(value.to_le_bytes());
+        }
+        chars.extend([glyph_page, 15]);
+        bytes.extend(block(4, &chars));
+
+        let mut kerning = Vec::new();
+        kerning.extend(66u32.to_le_bytes());
+        kerning.extend(65u32.to_le_bytes());
+        kerning.extend((-2i16).to_le_bytes());
+        bytes.extend(block(5, &kerning));
+        bytes
+    }
+
+    #[test]
+    fn text_format() {
+        let desc = BmFontDesc::parse(TEXT.as_bytes()).unwrap();
+        assert_eq!(desc.line_height, 10.0);
+        assert_eq!(desc.base, 8.0);
+        assert_eq!(desc.pages, ["font 0.png", "font_1.png"]);
+
+        let glyph = desc.glyphs[&65];
+        assert_eq!((glyph.x, glyph.y, glyph.width, glyph.height), (1, 2, 5, 7));
+        assert_eq!(glyph.offset, Vec2::new(0.0, 1.0));
+        assert_eq!(glyph.advance, 6.0);
+        assert_eq!(desc.glyphs[&u32::MAX].page, 1);
+        assert_eq!(desc.kerning[&(65, 86)], -1.0);
+    }
+
+    #[test]
+    fn binary_format() {
+        let desc = BmFontDesc::parse(&binary(1)).unwrap();
+        assert_eq!(desc.line_height, 12.0);
+        assert_eq!(desc.base, 9.0);
+        assert_eq!(desc.pages, ["a.png", "b.png"]);
+
+        let glyph = desc.glyphs[&66];
+        assert_eq!(glyph.page, 1);
+        assert_eq!((glyph.x, glyph.y, glyph.width, glyph.height), (3, 4, 5, 6));
+        assert_eq!(glyph.offset, Vec2::new(-1.0, 2.0));
+        assert_eq!(glyph.advance, 7.0);
+        assert_eq!(desc.kerning[&(66, 65)], -2.0);
+    }
+
+    #[test]
+    fn page_out_of_range() {
+        assert!(BmFontDesc::parse(&binary(2)).is_err());
+
+        let text = TEXT.replace("page=1", "page=2");
+        assert!(BmFontDesc::parse(text.as_bytes()).is_err());
+
+        let text = TEXT.replace("page id=1", "page id=-1");
+        assert!(BmFontDesc::parse(text.as_bytes()).is_err());
+        let text = TEXT.replace("page id=1", "page id=100000000000");
+        assert!(BmFontDesc::parse(text.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = binary(0);
+        // cutting the file between blocks leaves a valid font with less in it
+        let mut boundaries = vec![];
+        let mut offset = 4;
+        while offset < bytes.len() {
+            boundaries.push(offset);
+            let size = u32::from_le_bytes(bytes[offset + 1..offset + 5].try_into().unwrap());
+            offset += 5 + size as usize;
+        }
+        for len in (0..bytes.len()).filter(|len| !boundaries.contains(len)) {
+            assert!(BmFontDesc::parse(&bytes[..len]).is_err(), "{len} bytes");
+        }
+
+        assert!(BmFontDesc::parse(b"").is_err());
+        assert!(BmFontDesc::parse(b"common lineHeight=10").is_err());
+        assert!(BmFontDesc::parse(b"info face=x\nchar id=1 x=0").is_err());
+        assert!(BmFontDesc::parse(&[0xff, 0xfe]).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use bytemuck::{Pod, Zeroable};
+use vge_math::{Mat4, Rect, Vec2, Vec3};
+
+use crate::post::PostEffect;
+
+/// Orthographic 2D camera, y points up and the camera position
+/// ends up in the middle of the viewport
+#[derive(Clone, Copy, Debug)]
+pub struct Camera2D {
+    pub position: Vec2,
+    /// Values above 1 zoom in
+    pub zoom: f32,
+    /// Counter-clockwise, in radians
+    pub rotation: f32,
+    /// World units covered by a single pixel at zoom 1
+    pub units_per_pixel: f32,
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            zoom: 1.0,
+            rotation: 0.0,
+            units_per_pixel: 1.0,
+        }
+    }
+}
+
+impl Camera2D {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            ..Default::default()
+        }
+    }
+
+    /// World to clip space transform for a viewport of `size` pixels
+    pub fn view_projection(&self, size: Vec2) -> Mat4 {
+        let (sx, sy) = self.clip_scale(size);
+        let position = Vec3::new(-self.position.x, -self.position.y, 0.0);
+
+        Mat4::from_scale(Vec3::new(sx, sy, 1.0))
+            * Mat4::from_rotation_z(-self.rotation)
+            * Mat4::from_translation(position)
+    }
+
+    /// Converts a point in pixels, origin at the top left of the viewport, to world space
+    pub fn screen_to_world(&self, point: Vec2, size: Vec2) -> Vec2 {
+        let (sx, sy) = self.clip_scale(size);
+        let (sin, cos) = self.rotation.sin_cos();
+
+        let x = (point.x / size.x * 2.0 - 1.0) / sx;
+        let y = (1.0 - point.y / size.y * 2.0) / sy;
+
+        Vec2::new(
+            self.position.x + cos * x - sin * y,
+            self.position.y + sin * x + cos * y,
+        )
+    }
+
+    /// Converts a point in world space to pixels, origin at the top left of the viewport
+    pub fn world_to_screen(&self, point: Vec2, size: Vec2) -> Vec2 {
+        let (sx, sy) = self.clip_scale(size);
+        let (sin, cos) = self.rotation.sin_cos();
+
+        let dx = point.x - self.position.x;
+        let dy = point.y - self.position.y;
+        let x = (cos * dx + sin * dy) * sx;
+        let y = (-sin * dx + cos * dy) * sy;
+
+        Vec2::new((x + 1.0) * 0.5 * size.x, (1.0 - y) * 0.5 * size.y)
+    }
+
+    fn clip_scale(&self, size: Vec2) -> (f32, f32) {
+        let units = self.units_per_pixel / self.zoom;
+        (
+            2.0 / (size.x.max(1.0) * units),
+            2.0 / (size.y.max(1.0) * units),
+        )
+    }
+}
+
+/// Part of the render target drawn through its own camera,
+/// every viewport draws the whole frame
+#[derive(Clone, Debug)]
+pub struct Viewport {
+    /// Normalized to the render target, (0, 0) is the top left
+    pub rect: Rect,
+    pub camera: Camera2D,
+    /// Run over the viewport in order once the frame is drawn, only
+    /// for the window and not for [`RenderTarget`](crate::RenderTarget)s
+    pub post: Vec<PostEffect>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::new(Camera2D::default())
+    }
+}
+
+impl Viewport {
+    /// Covers the whole render target
+    pub fn new(camera: Camera2D) -> Self {
+        Self::with_rect(Rect::new(Vec2::ZERO, Vec2::ONE), camera)
+    }
+
+    pub fn with_rect(rect: Rect, camera: Camera2D) -> Self {
+        Self {
+            rect,
+            camera,
+            post: Vec::new(),
+        }
+    }
+
+    pub fn post(mut self, effects: Vec<PostEffect>) -> Self {
+        self.post = effects;
+        self
+    }
+
+    /// Viewport in pixels for a render target of `target_size`
+    pub fn pixel_rect(&self, target_size: Vec2) -> Rect {
+        Rect::new(self.rect.min * target_size, self.rect.max * target_size)
+    }
+
+    /// Converts a point in pixels of the whole render target to world space
+    pub fn screen_to_world(&self, point: Vec2, target_size: Vec2) -> Vec2 {
+        let rect = self.pixel_rect(target_size);
+        self.camera
+            .screen_to_world(point - rect.min, rect.size().max(Vec2::ONE))
+    }
+
+    /// Part of [`Viewport::pixel_rect`] inside the render target in whole pixels,
+    /// what actually gets drawn to. `None` if nothing's left
+    pub(crate) fn visible_rect(&self, target_size: Vec2) -> Option<Rect> {
+        let rect = self.pixel_rect(target_size);
+        let min = rect.min.round().max(Vec2::ZERO).min(target_size);
+        let max = rect.max.round().max(Vec2::ZERO).min(target_size);
+        let rect = Rect::new(min, max);
+        (!rect.is_empty()).then_some(rect)
+    }
+
+    /// World to clip space transform for drawing into [`Viewport::visible_rect`],
+    /// parts of the viewport outside the render target get cut off rather than squashed
+    pub(crate) fn view_projection(&self, target_size: Vec2) -> Mat4 {
+        let rect = self.pixel_rect(target_size);
+        let projection = self.camera.view_projection(rect.size().max(Vec2::ONE));
+        let Some(visible) = self.visible_rect(target_size) else {
+            return projection;
+        };
+
+        // maps the clip space of the whole viewport onto the visible part
+        let scale = rect.size() / visible.size();
+        let offset = (rect.min + rect.max - visible.min - visible.max) / visible.size();
+        Mat4::from_translation(Vec3::new(offset.x, -offset.y, 0.0))
+            * Mat4::from_scale(Vec3::new(scale.x, scale.y, 1.0))
+            * projection
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Zeroable, Pod)]
+struct CameraUniform {
+    view_proj: Mat4,
+}
+
+/// One camera uniform buffer and bind group per viewport
+pub(crate) struct CameraBindings {
+    layout: wgpu::BindGroupLayout,
+    bindings: Vec<(wgpu::Buffer, wgpu::BindGroup)>,
+}
+
+impl CameraBindings {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Camera bind group layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        Self {
+            layout,
+            bindings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn layout(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    /// Uploads the camera of every viewport
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        viewports: &[Viewport],
+        target_size: Vec2,
+    ) {
+        while self.bindings.len() < viewports.len() {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Camera buffer"),
+                size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Camera bind group"),
+                layout: &self.layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+
+            self.bindings.push((buffer, bind_group));
+        }
+
+        for (viewport, (buffer, _)) in viewports.iter().zip(&self.bindings) {
+            let uniform = CameraUniform {
+                view_proj: viewport.view_projection(target_size),
+            };
+            queue.write_buffer(buffer, 0, bytemuck::bytes_of(&uniform));
+        }
+    }
+
+    pub(crate) fn bind_group(&self, index: usize) -> &wgpu::BindGroup {
+        &self.bindings[index].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!((a - b).length() < 1e-3, "{a:?} != {b:?}");
+    }
+
+    fn minimap() -> Viewport {
+        let camera = Camera2D {
+            position: Vec2::new(30.0, -10.0),
+            zoom: 2.0,
+            ..Default::default()
+        };
+        Viewport::with_rect(
+            Rect::new(Vec2::new(-0.25, 0.5), Vec2::new(0.25, 1.0)),
+            camera,
+        )
+    }
+
+    #[test]
+    fn screen_to_world_ignores_clipping() {
+        let viewport = minimap();
+        let target = Vec2::new(800.0, 600.0);
+        let rect = viewport.pixel_rect(target);
+        assert_eq!(rect.min, Vec2::new(-200.0, 300.0));
+
+        for point in [
+            Vec2::new(0.0, 300.0),
+            Vec2::new(100.0, 450.0),
+            Vec2::new(200.0, 600.0),
+        ] {
+            let expected = viewport
+                .camera
+                .screen_to_world(point - rect.min, rect.size());
+            assert_close(viewport.screen_to_world(point, target), expected);
+        }
+
+        // the middle of the viewport is off the target, but it's still the camera position
+        assert_close(
+            viewport.screen_to_world(rect.center(), target),
+            viewport.camera.position,
+        );
+    }
+
+    #[test]
+    fn view_projection_clips() {
+        let viewport = minimap();
+        let target = Vec2::new(800.0, 600.0);
+        let visible = viewport.visible_rect(target).unwrap();
+        assert_eq!(
+            visible,
+            Rect::new(Vec2::new(0.0, 300.0), Vec2::new(200.0, 600.0))
+        );
+
+        let projection = viewport.view_projection(target);
+        // world points land on the same pixels they would without clipping
+        for point in [
+            Vec2::new(0.0, 300.0),
+            Vec2::new(50.0, 400.0),
+            Vec2::new(200.0, 600.0),
+        ] {
+            let world = viewport.screen_to_world(point, target);
+            let clip = projection.transform_point3(Vec3::new(world.x, world.y, 0.0));
+            let pixel = Vec2::new(
+                visible.min.x + (clip.x + 1.0) * 0.5 * visible.width(),
+                visible.min.y + (1.0 - clip.y) * 0.5 * visible.height(),
+            );
+            assert_close(pixel, point);
+        }
+
+        // nothing to clip matches the camera's own projection
+        let full = Viewport::new(viewport.camera);
+        assert_eq!(
+            full.view_projection(target),
+            full.camera.view_projection(target)
+        );
+    }
+}
@@ -1,15 +1,60 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+    time::Instant,
+};
 
-use mesh::{Sprite, TexturedQuad};
-use primitives::{Primitive, Quad, Vertex, VertexTextured};
+use batch::{DrawCall, ShapeBatch, SpriteBatch};
+use camera::CameraBindings;
+use material::ShaderInner;
+use mesh::{DrawSprite, Text};
+use post::PostProcessor;
+use primitives::{Vertex, VertexColored, VertexTextured};
+use text::GlyphAtlas;
 use thiserror::Error;
-use vge_math::{Rect, Vec2};
-use wgpu::{
-    CreateSurfaceError, ShaderModuleDescriptor, SurfaceTarget, include_wgsl, util::DeviceExt,
-};
+use upscale::Blit;
+use vge_math::Vec2;
+use watch::{FileWatcher, POLL_INTERVAL};
+use wgpu::{CreateSurfaceError, ShaderModuleDescriptor, SurfaceTarget, include_wgsl};
 
+pub mod animation;
+pub mod aseprite;
+pub mod assets;
+pub mod atlas;
+mod batch;
+mod bitmap_font;
+pub mod camera;
+mod capture;
+pub mod material;
 pub mod mesh;
-mod primitives;
+pub mod mipmap;
+pub mod paint;
+mod pool;
+pub mod post;
+pub mod primitives;
+mod reader;
+pub mod record;
+pub mod sampler;
+pub mod target;
+pub mod text;
+pub mod upscale;
+mod watch;
+
+pub use animation::{AnimatedSprite, Clip, PlayMode, SpriteSheet};
+pub use aseprite::Aseprite;
+pub use assets::{Asset, Assets, Handle, LoadState};
+pub use atlas::{AtlasBuilder, AtlasRegion, TextureAtlas};
+pub use camera::{Camera2D, Viewport};
+pub use image;
+pub use material::{Material, Shader, ShaderBinding, ShaderKind};
+pub use paint::Painter;
+pub use post::PostEffect;
+pub use primitives::Color;
+pub use record::FrameRecorder;
+pub use sampler::{Filter, Sampler, Wrap};
+pub use target::{RenderTarget, TargetDraw};
+pub use text::{Align, Font};
+pub use wgpu::SurfaceError;
 
 const COLORED_SHADER: ShaderModuleDescriptor =
     include_wgsl!("../../../assets/shaders/colored.wgsl");
@@ -17,7 +62,10 @@ const COLORED_SHADER: ShaderModuleDescriptor =
 const TEXTURED_SHADER: ShaderModuleDescriptor =
     include_wgsl!("../../../assets/shaders/textured.wgsl");
 
-const LOGO: &[u8] = include_bytes!("../../../assets/images/vge_logo_9x.png");
+/// Where the built-in shaders are looked for in the asset root while hot reloading,
+/// the same place they are in vge's own assets
+const COLORED_SHADER_FILE: &str = "shaders/colored.wgsl";
+const TEXTURED_SHADER_FILE: &str = "shaders/textured.wgsl";
 
 pub fn wgpu<'a>(
     target: impl Into<SurfaceTarget<'a>>,
@@ -27,26 +75,100 @@ pub fn wgpu<'a>(
     Ok(gfx)
 }
 
+/// Creates a [`Gfx`] that renders into an offscreen texture instead of a window,
+/// read the result back with [`Gfx::read_frame`]
+pub fn headless(size: (u32, u32)) -> Result<Gfx<'static>, RenderError> {
+    let gfx = Gfx::new_headless(size)?;
+    Ok(gfx)
+}
+
+enum Target<'a> {
+    Surface(wgpu::Surface<'a>),
+    Offscreen(wgpu::Texture),
+}
+
+/// How frames are queued up for presentation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Wait for vertical blank, no tearing
+    #[default]
+    Vsync,
+    /// Present right away, may tear
+    Immediate,
+    /// Replace the queued frame without tearing, falls back to
+    /// [`PresentMode::Immediate`] where unsupported
+    Mailbox,
+}
+
+/// Everything drawn in a single frame
+#[derive(Clone, Default)]
+pub struct Frame {
+    pub sprites: Vec<DrawSprite>,
+    pub painter: Painter,
+    pub texts: Vec<Text>,
+    /// Drawn into their render targets before the rest of the frame, in order
+    pub targets: Vec<TargetDraw>,
+    /// Clears to this instead of [`Gfx::set_clear_color`] for just this frame
+    pub clear_color: Option<Color>,
+}
+
+impl Frame {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+        self.painter.clear();
+        self.texts.clear();
+        self.targets.clear();
+        self.clear_color = None;
+    }
+}
+
 pub struct Gfx<'a> {
-    instance: wgpu::Instance,
     adapter: wgpu::Adapter,
-    pub(crate) device: wgpu::Device,
-    queue: wgpu::Queue,
-    surface: wgpu::Surface<'a>,
+    pub(crate) device: Arc<wgpu::Device>,
+    pub(crate) queue: Arc<wgpu::Queue>,
+    target: Target<'a>,
     surface_configured: bool,
     config: wgpu::SurfaceConfiguration,
     pipeline: wgpu::RenderPipeline,
-    // vbuf: wgpu::Buffer,
-    // ibuf: wgpu::Buffer,
-    // num_indices: u32,
-    diffuse_bind_group: wgpu::BindGroup,
+    shape_pipeline: wgpu::RenderPipeline,
+    sprite_batch: SpriteBatch,
+    shape_batch: ShapeBatch,
+    glyph_atlas: GlyphAtlas,
+    cameras: CameraBindings,
+    viewports: Vec<Viewport>,
+    pub(crate) texture_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    clear_color: Color,
+    /// Set while drawing at a fixed low resolution, see [`Gfx::set_pixel_perfect`]
+    pixel_target: Option<RenderTarget>,
+    /// Draws the pixel-perfect or post-processed scene onto the window
+    blit: Blit,
+    post: PostProcessor,
+    /// Post-process shaders see the time since this
+    started: Instant,
+    /// Copy of the last frame presented to the window when it was kept, for [`Gfx::screenshot`]
+    last_frame: Option<wgpu::Texture>,
+    /// Set by [`Gfx::capture_next_frame`], window frames are only copied when asked to
+    capture_next: bool,
+    recorder: Option<FrameRecorder>,
+    assets: Assets,
+    /// Keeps what [`Gfx::create_sprite`] loaded in the asset cache
+    sprites: Mutex<Vec<Handle<mesh::Sprite>>>,
+    /// Watches the built-in shader sources, only while hot reloading
+    shader_watcher: Option<FileWatcher>,
+    last_shader_poll: Instant,
+    /// Shaders loaded from files, recompiled when they change while hot reloading
+    shaders: Mutex<Vec<Weak<ShaderInner>>>,
 }
 
 impl<'a> Gfx<'a> {
     fn new(target: impl Into<SurfaceTarget<'a>>, size: (u32, u32)) -> Result<Self, RenderError> {
-        let instance = Self::create_instance();
+        let instance = Self::create_instance(wgpu::Backends::PRIMARY);
         let surface = Self::create_surface(&instance, target)?;
-        let adapter = Self::create_adapter(&instance, &surface)?;
+        let adapter = Self::create_adapter(&instance, Some(&surface), false)?;
         let (device, queue) = Self::create_device(&adapter)?;
 
         let surface_caps = surface.get_capabilities(&adapter);
@@ -58,8 +180,12 @@ impl<'a> Gfx<'a> {
             .copied()
             .unwrap_or(surface_caps.formats[0]);
 
+        // copied out of after every frame for screenshots, where supported
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
+
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format: surface_format,
             width: size.0,
             height: size.1,
@@ -69,9 +195,41 @@ impl<'a> Gfx<'a> {
             view_formats: vec![],
         };
 
-        // image
-        let logo = TexturedQuad::from_bytes(&device, &queue, LOGO, "logo image")?;
+        Self::with_target(adapter, device, queue, Target::Surface(surface), config)
+    }
+
+    fn new_headless(size: (u32, u32)) -> Result<Self, RenderError> {
+        // any backend will do without a window, software rasterizers included
+        let instance = Self::create_instance(wgpu::Backends::all());
+        let adapter = Self::create_adapter(&instance, None, false)
+            .or_else(|_| Self::create_adapter(&instance, None, true))?;
+        let (device, queue) = Self::create_device(&adapter)?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.0,
+            height: size.1,
+            present_mode: wgpu::PresentMode::Fifo,
+            desired_maximum_frame_latency: 2,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        };
+
+        let texture = Self::create_offscreen_texture(&device, &config);
+        let mut gfx =
+            Self::with_target(adapter, device, queue, Target::Offscreen(texture), config)?;
+        gfx.surface_configured = size.0 > 0 && size.1 > 0;
+        Ok(gfx)
+    }
 
+    fn with_target(
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        target: Target<'a>,
+        config: wgpu::SurfaceConfiguration,
+    ) -> Result<Self, RenderError> {
         let texture_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 label: Some("Texture bind group layout"),
@@ -95,50 +253,70 @@ impl<'a> Gfx<'a> {
                 ],
             });
 
-        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Diffuse bind group"),
-            layout: &texture_bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&logo.view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&logo.sampler),
-                },
-            ],
-        });
+        let cameras = CameraBindings::new(&device);
 
         // pipeline
         let shader = device.create_shader_module(TEXTURED_SHADER);
-        let pipeline = Self::create_pipeline::<VertexTextured>(&device, &config, &shader, &[
-            &texture_bind_group_layout,
-        ]);
+        let pipeline = Self::create_pipeline::<VertexTextured>(
+            &device,
+            &config,
+            &shader,
+            &[cameras.layout(), &texture_bind_group_layout],
+        );
+
+        let shader = device.create_shader_module(COLORED_SHADER);
+        let shape_pipeline =
+            Self::create_pipeline::<VertexColored>(&device, &config, &shader, &[cameras.layout()]);
 
-        // let quad = Quad::colored(Rect::new(Vec2::new(-0.5, -0.5), Vec2::new(0.5, 0.5)));
+        let sprite_batch = SpriteBatch::new(&device);
+        let shape_batch = ShapeBatch::new(&device);
+        let glyph_atlas = GlyphAtlas::new(&device, &texture_bind_group_layout);
+        let blit = Blit::new(&device, &texture_bind_group_layout, config.format);
+        let post = PostProcessor::new(&device);
 
-        // let num_indices = quad.indices().unwrap().len() as u32;
+        let device = Arc::new(device);
+        let queue = Arc::new(queue);
+        let texture_bind_group_layout = Arc::new(texture_bind_group_layout);
+        let assets = Assets::new(
+            device.clone(),
+            queue.clone(),
+            texture_bind_group_layout.clone(),
+        );
 
         Ok(Self {
-            instance,
             adapter,
             device,
             queue,
-            surface,
+            target,
             config,
             surface_configured: false,
             pipeline,
-            // vbuf,
-            // ibuf,
-            // num_indices,
-            diffuse_bind_group,
+            shape_pipeline,
+            sprite_batch,
+            shape_batch,
+            glyph_atlas,
+            cameras,
+            viewports: vec![Viewport::default()],
+            texture_bind_group_layout,
+            clear_color: Color::new(0.1, 0.2, 0.3),
+            pixel_target: None,
+            blit,
+            post,
+            started: Instant::now(),
+            last_frame: None,
+            capture_next: false,
+            recorder: None,
+            assets,
+            sprites: Mutex::new(Vec::new()),
+            shader_watcher: None,
+            last_shader_poll: Instant::now(),
+            shaders: Mutex::new(Vec::new()),
         })
     }
 
-    fn create_instance() -> wgpu::Instance {
+    fn create_instance(backends: wgpu::Backends) -> wgpu::Instance {
         wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
+            backends,
             ..Default::default()
         })
     }
@@ -155,12 +333,13 @@ impl<'a> Gfx<'a> {
 
     fn create_adapter(
         instance: &wgpu::Instance,
-        surface: &wgpu::Surface,
+        surface: Option<&wgpu::Surface>,
+        force_fallback_adapter: bool,
     ) -> Result<wgpu::Adapter, RenderError> {
         let adapter = smol::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
             power_preference: Default::default(),
-            compatible_surface: Some(surface),
-            force_fallback_adapter: false,
+            compatible_surface: surface,
+            force_fallback_adapter,
         }))
         .ok_or(RenderError::Adapter)?;
         Ok(adapter)
@@ -180,6 +359,26 @@ impl<'a> Gfx<'a> {
         Ok(result)
     }
 
+    fn create_offscreen_texture(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen target"),
+            size: wgpu::Extent3d {
+                width: config.width.max(1),
+                height: config.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        })
+    }
+
     fn create_pipeline<V: Vertex>(
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
@@ -215,7 +414,8 @@ impl<'a> Gfx<'a> {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
+                // sprites flipped with a negative scale face the other way
+                cull_mode: None,
                 unclipped_depth: false,
                 polygon_mode: wgpu::PolygonMode::Fill,
                 conservative: false,
@@ -231,27 +431,524 @@ impl<'a> Gfx<'a> {
         })
     }
 
+    /// Same as [`Gfx::create_pipeline`] for WGSL that may not compile,
+    /// errors come back instead of panicking
+    fn try_create_pipeline<V: Vertex>(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        label: &str,
+        source: &str,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> Result<wgpu::RenderPipeline, RenderError> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipeline = Self::create_pipeline::<V>(device, config, &shader, bind_group_layouts);
+        match smol::block_on(device.pop_error_scope()) {
+            Some(error) => Err(RenderError::Shader(error.to_string())),
+            None => Ok(pipeline),
+        }
+    }
+
+    /// Picks up changes to loaded assets and shaders while running,
+    /// shaders that don't compile get reported and the last working one stays
+    ///
+    /// The built-in shaders are replaced once `shaders/colored.wgsl` or
+    /// `shaders/textured.wgsl` in the [asset root](Assets::root) is written to
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.assets.set_hot_reload(enabled);
+        let root = self.assets.root();
+        self.shader_watcher = enabled.then(|| {
+            let mut watcher = FileWatcher::default();
+            watcher.watch(&root.join(COLORED_SHADER_FILE));
+            watcher.watch(&root.join(TEXTURED_SHADER_FILE));
+            watcher
+        });
+    }
+
+    pub fn hot_reload(&self) -> bool {
+        self.shader_watcher.is_some()
+    }
+
+    /// Pipeline for a material shader, the bind groups that come with
+    /// its kind go in front of the material's own
+    pub(crate) fn material_pipeline(
+        &self,
+        kind: ShaderKind,
+        label: &str,
+        source: &str,
+        material_layout: Option<&wgpu::BindGroupLayout>,
+    ) -> Result<wgpu::RenderPipeline, RenderError> {
+        let mut layouts = vec![self.cameras.layout()];
+        if kind == ShaderKind::Sprite {
+            layouts.push(&self.texture_bind_group_layout);
+        }
+        layouts.extend(material_layout);
+
+        match kind {
+            // group 0 is what's being processed instead of the camera
+            ShaderKind::PostProcess => self.post.create_pipeline(
+                &self.device,
+                self.format(),
+                label,
+                source,
+                material_layout,
+            ),
+            ShaderKind::Sprite => Self::try_create_pipeline::<VertexTextured>(
+                &self.device,
+                &self.config,
+                label,
+                source,
+                &layouts,
+            ),
+            ShaderKind::Shape => Self::try_create_pipeline::<VertexColored>(
+                &self.device,
+                &self.config,
+                label,
+                source,
+                &layouts,
+            ),
+        }
+    }
+
+    pub(crate) fn watch_shader(&self, shader: &Shader) {
+        self.shaders
+            .lock()
+            .unwrap()
+            .push(Arc::downgrade(&shader.inner));
+    }
+
+    fn reload_shaders(&mut self) {
+        let Some(watcher) = &mut self.shader_watcher else {
+            return;
+        };
+        if self.last_shader_poll.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        self.last_shader_poll = Instant::now();
+
+        let shaders: Vec<Shader> = {
+            let mut shaders = self.shaders.lock().unwrap();
+            shaders.retain(|shader| shader.strong_count() > 0);
+            shaders
+                .iter()
+                .filter_map(Weak::upgrade)
+                .map(|inner| Shader { inner })
+                .collect()
+        };
+        let loaded_from = |shader: &Shader, path: &Path| shader.inner.path.as_deref() == Some(path);
+
+        // looked up every time since the root can change
+        let root = self.assets.root();
+        let colored_path = root.join(COLORED_SHADER_FILE);
+        let textured_path = root.join(TEXTURED_SHADER_FILE);
+
+        watcher.retain(|path| {
+            path == colored_path
+                || path == textured_path
+                || shaders.iter().any(|shader| loaded_from(shader, path))
+        });
+        watcher.watch(&colored_path);
+        watcher.watch(&textured_path);
+        for path in shaders
+            .iter()
+            .filter_map(|shader| shader.inner.path.as_ref())
+        {
+            watcher.watch(path);
+        }
+
+        for path in watcher.changed() {
+            let result = std::fs::read_to_string(&path)
+                .map_err(RenderError::from)
+                .and_then(|source| {
+                    if path == textured_path {
+                        self.pipeline = Self::try_create_pipeline::<VertexTextured>(
+                            &self.device,
+                            &self.config,
+                            "textured.wgsl",
+                            &source,
+                            &[self.cameras.layout(), &self.texture_bind_group_layout],
+                        )?;
+                    } else if path == colored_path {
+                        self.shape_pipeline = Self::try_create_pipeline::<VertexColored>(
+                            &self.device,
+                            &self.config,
+                            "colored.wgsl",
+                            &source,
+                            &[self.cameras.layout()],
+                        )?;
+                    }
+
+                    for shader in shaders.iter().filter(|shader| loaded_from(shader, &path)) {
+                        let pipeline = self.material_pipeline(
+                            shader.kind(),
+                            &path.to_string_lossy(),
+                            &source,
+                            shader.inner.layout.as_ref(),
+                        )?;
+                        *shader.inner.pipeline.write().unwrap() = Arc::new(pipeline);
+                    }
+                    Ok(())
+                });
+
+            match result {
+                Ok(()) => tracing::info!("reloaded {}", path.display()),
+                Err(error) => tracing::error!("could not reload {}: {error}", path.display()),
+            }
+        }
+    }
+
+    /// A width or height of 0, like a minimized window has, pauses
+    /// rendering until the next non-zero size
     pub fn set_surface_size(&mut self, width: u32, height: u32) {
+        // wgpu can't create zero sized textures or surfaces
+        if width == 0 || height == 0 {
+            self.surface_configured = false;
+            return;
+        }
+
         self.config.width = width;
         self.config.height = height;
-        self.surface.configure(&self.device, &self.config);
+        match &mut self.target {
+            Target::Surface(surface) => surface.configure(&self.device, &self.config),
+            Target::Offscreen(texture) => {
+                *texture = Self::create_offscreen_texture(&self.device, &self.config)
+            }
+        }
         self.surface_configured = true;
     }
 
-    // TODO: render list of meshes
-    pub fn render(&mut self, sprites: &[Sprite]) -> Result<(), RenderError> {
+    pub fn size(&self) -> (u32, u32) {
+        (self.config.width, self.config.height)
+    }
+
+    /// Format of the window and render targets, pipelines are made for it
+    pub(crate) fn format(&self) -> wgpu::TextureFormat {
+        self.config.format
+    }
+
+    /// Offscreen targets are never presented, so this only affects window surfaces
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        let Target::Surface(surface) = &self.target else {
+            return;
+        };
+
+        let supported = surface.get_capabilities(&self.adapter).present_modes;
+        self.config.present_mode = match mode {
+            PresentMode::Vsync => wgpu::PresentMode::AutoVsync,
+            PresentMode::Mailbox if supported.contains(&wgpu::PresentMode::Mailbox) => {
+                wgpu::PresentMode::Mailbox
+            }
+            PresentMode::Immediate | PresentMode::Mailbox => wgpu::PresentMode::AutoNoVsync,
+        };
+
+        if self.surface_configured {
+            surface.configure(&self.device, &self.config);
+        }
+    }
+
+    /// Draws everything through a single camera covering the whole target
+    pub fn set_camera(&mut self, camera: Camera2D) {
+        self.viewports = vec![Viewport::new(camera)];
+    }
+
+    /// Draws everything once per viewport, in order
+    pub fn set_viewports(&mut self, viewports: Vec<Viewport>) {
+        self.viewports = viewports;
+    }
+
+    pub fn viewports(&self) -> &[Viewport] {
+        &self.viewports
+    }
+
+    /// Color frames are cleared to unless they set [`Frame::clear_color`]
+    pub fn set_clear_color(&mut self, color: Color) {
+        self.clear_color = color;
+    }
+
+    pub fn clear_color(&self) -> Color {
+        self.clear_color
+    }
+
+    /// What new textures are sampled with, textures already created keep theirs
+    pub fn default_sampler(&self) -> Sampler {
+        self.assets.sampler()
+    }
+
+    pub fn set_default_sampler(&mut self, sampler: Sampler) {
+        self.assets.set_sampler(sampler);
+    }
+
+    /// Whether new textures get a generated mip chain, see [`Gfx::set_mipmaps`]
+    pub fn mipmaps(&self) -> bool {
+        self.assets.mipmaps()
+    }
+
+    /// Generates mipmaps for textures loaded from now on, pair it with
+    /// [`Sampler::linear`] as the default sampler for trilinear filtering
+    pub fn set_mipmaps(&mut self, enabled: bool) {
+        self.assets.set_mipmaps(enabled);
+    }
+
+    /// Draws everything at `resolution` and scales it up to the window by a whole
+    /// number with nearest filtering, so every texel stays a crisp square
+    ///
+    /// Also switches the default sampler to [`Sampler::nearest`], `None` goes
+    /// back to drawing at the window size but leaves the sampler alone
+    pub fn set_pixel_perfect(&mut self, resolution: Option<(u32, u32)>) {
+        if self.pixel_perfect() == resolution {
+            return;
+        }
+        self.pixel_target = resolution.map(|resolution| {
+            RenderTarget::create(
+                &self.device,
+                &self.texture_bind_group_layout,
+                self.format(),
+                resolution,
+                Sampler::nearest(),
+                Some("Pixel-perfect target"),
+            )
+        });
+        if resolution.is_some() {
+            self.set_default_sampler(Sampler::nearest());
+        }
+    }
+
+    pub fn pixel_perfect(&self) -> Option<(u32, u32)> {
+        self.pixel_target.as_ref().map(RenderTarget::size)
+    }
+
+    /// Size the scene is drawn at, the pixel-perfect resolution if set
+    /// or the size of the target otherwise
+    pub fn resolution(&self) -> (u32, u32) {
+        self.pixel_perfect().unwrap_or(self.size())
+    }
+
+    /// Reads the last rendered frame back from a headless [`Gfx`]
+    pub fn read_frame(&self) -> Result<image::RgbaImage, RenderError> {
+        let Target::Offscreen(texture) = &self.target else {
+            return Err(RenderError::NotHeadless);
+        };
+
+        capture::read_texture(&self.device, &self.queue, texture)
+    }
+
+    /// Keeps a copy of the next frame rendered to the window for [`Gfx::screenshot`],
+    /// frames are also kept while recording. Headless frames can always be read
+    pub fn capture_next_frame(&mut self) {
+        self.capture_next = true;
+    }
+
+    /// Reads back the last frame that was rendered to a headless [`Gfx`]
+    ///
+    /// Window frames can't be read after presenting, call [`Gfx::capture_next_frame`]
+    /// before the render to screenshot. Gives [`RenderError::NoFrame`] if the last
+    /// frame presented wasn't kept, rather than an older one
+    pub fn screenshot(&self) -> Result<image::RgbaImage, RenderError> {
+        let texture = match &self.target {
+            Target::Offscreen(texture) => texture,
+            Target::Surface(_) if !self.config.usage.contains(wgpu::TextureUsages::COPY_SRC) => {
+                return Err(RenderError::NoReadback);
+            }
+            Target::Surface(_) => self.last_frame.as_ref().ok_or(RenderError::NoFrame)?,
+        };
+
+        capture::read_texture(&self.device, &self.queue, texture)
+    }
+
+    /// Saves what [`Gfx::screenshot`] reads, the format follows the extension of `path`
+    pub fn save_screenshot(&self, path: impl AsRef<Path>) -> Result<(), RenderError> {
+        self.screenshot()?.save(path)?;
+        Ok(())
+    }
+
+    /// Saves every frame rendered from now on into `dir` as numbered PNGs,
+    /// see [`FrameRecorder::capture`]. Replaces any recording already going
+    pub fn start_recording(
+        &mut self,
+        dir: impl Into<PathBuf>,
+        fps: u32,
+    ) -> Result<(), RenderError> {
+        self.stop_recording()?;
+        self.recorder = Some(FrameRecorder::new(dir, fps)?);
+        Ok(())
+    }
+
+    /// Waits for the frames recorded so far to be written, does nothing when not recording
+    pub fn stop_recording(&mut self) -> Result<(), RenderError> {
+        match self.recorder.take() {
+            Some(recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Draws `frame`, its [`Frame::targets`] included, rendering the same frame
+    /// again draws into the targets again
+    pub fn render(&mut self, frame: &Frame) -> Result<(), RenderError> {
+        self.reload_shaders();
+
         if !self.surface_configured {
             return Ok(());
         }
 
-        let output = self
-            .surface
-            .get_current_texture()
-            .map_err(RenderError::Surface)?;
+        let (output, view) = match &self.target {
+            Target::Surface(surface) => {
+                let output = surface
+                    .get_current_texture()
+                    .map_err(RenderError::Surface)?;
+                let view = output
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                (Some(output), view)
+            }
+            Target::Offscreen(texture) => {
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                (None, view)
+            }
+        };
+
+        for draw in &frame.targets {
+            self.render_to(draw);
+        }
+
+        // with pixel-perfect or post-processing on the scene goes to a texture first,
+        // which then gets scaled up or processed onto the window
+        let viewports = self.viewports.clone();
+        let clear_color = Some(frame.clear_color.unwrap_or(self.clear_color));
+        let post = viewports.iter().any(|viewport| !viewport.post.is_empty());
+        let scene = if post {
+            Some(self.post.scene(
+                &self.device,
+                &self.texture_bind_group_layout,
+                self.format(),
+                self.resolution(),
+            ))
+        } else {
+            self.pixel_target.clone()
+        };
+        match scene {
+            Some(scene) => {
+                self.draw_scene(&scene.view(), scene.size(), frame, &viewports, clear_color);
+                let result = if post {
+                    let time = self.started.elapsed().as_secs_f32();
+                    self.post.run(&self.device, &self.queue, &viewports, time)
+                } else {
+                    scene
+                };
+                self.blit
+                    .draw(&self.device, &self.queue, &result, &view, self.size());
+            }
+            None => self.draw_scene(&view, self.size(), frame, &viewports, clear_color),
+        }
+
+        if let Some(output) = output {
+            let capture = self.capture_next || self.recorder.is_some();
+            if capture && self.config.usage.contains(wgpu::TextureUsages::COPY_SRC) {
+                self.keep_frame(&output.texture);
+            } else {
+                self.last_frame = None;
+            }
+            self.capture_next = false;
+            output.present();
+        }
+
+        if let Some(mut recorder) = self.recorder.take() {
+            if let Err(error) = recorder.capture(self) {
+                tracing::error!("could not record frame: {error}");
+            }
+            self.recorder = Some(recorder);
+        }
+
+        Ok(())
+    }
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+    /// Copies a window frame about to be presented into `last_frame`, since
+    /// surface textures can't be read after presenting
+    fn keep_frame(&mut self, texture: &wgpu::Texture) {
+        let size = texture.size();
+        let last_frame = match self.last_frame.take() {
+            Some(last_frame) if last_frame.size() == size => last_frame,
+            _ => self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Last frame"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: texture.format(),
+                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            }),
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Last frame encoder"),
+            });
+        encoder.copy_texture_to_texture(texture.as_image_copy(), last_frame.as_image_copy(), size);
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        self.last_frame = Some(last_frame);
+    }
+
+    /// Draws into a [`RenderTarget`] right away, the same happens for the
+    /// [`Frame::targets`] of a frame when it's rendered
+    pub fn render_to(&mut self, draw: &TargetDraw) {
+        for nested in &draw.frame.targets {
+            self.render_to(nested);
+        }
+        self.draw_scene(
+            &draw.target.view(),
+            draw.target.size(),
+            &draw.frame,
+            &draw.viewports,
+            draw.frame.clear_color,
+        );
+    }
+
+    /// Reads back what was drawn into a [`RenderTarget`]
+    pub fn read_target(&self, target: &RenderTarget) -> Result<image::RgbaImage, RenderError> {
+        capture::read_texture(&self.device, &self.queue, target.texture())
+    }
+
+    /// Draws the sprites, shapes and text of `frame` into `view` and submits it,
+    /// keeps what's in `view` without a `clear` color
+    ///
+    /// Each call submits on its own since the batches get reused between them
+    fn draw_scene(
+        &mut self,
+        view: &wgpu::TextureView,
+        (width, height): (u32, u32),
+        frame: &Frame,
+        viewports: &[Viewport],
+        clear: Option<Color>,
+    ) {
+        let target_size = Vec2::new(width as f32, height as f32);
+        let load = match clear {
+            Some(color) => wgpu::LoadOp::Clear(color.into()),
+            None => wgpu::LoadOp::Load,
+        };
+        let glyphs = self.glyph_atlas.prepare(
+            &self.device,
+            &self.queue,
+            &self.texture_bind_group_layout,
+            &frame.texts,
+        );
+        self.sprite_batch.prepare(
+            &self.device,
+            &self.queue,
+            frame.sprites.iter().chain(&glyphs),
+        );
+        self.shape_batch
+            .prepare(&self.device, &self.queue, &frame.painter);
+        self.cameras
+            .prepare(&self.device, &self.queue, viewports, target_size);
 
         let mut encoder = self
             .device
@@ -263,15 +960,10 @@ impl<'a> Gfx<'a> {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
+                        load,
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -280,29 +972,89 @@ impl<'a> Gfx<'a> {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
-
-            for spr in sprites {
-                render_pass
-                    .set_index_buffer(spr.texture.idx_buf.slice(..), wgpu::IndexFormat::Uint16);
-                render_pass.set_vertex_buffer(0, spr.texture.vtx_buf.slice(..));
-                render_pass.draw_indexed(
-                    0..spr.texture.quad.indices().unwrap().len() as u32,
-                    0,
-                    0..1,
+            let calls = batch::draw_order(&self.sprite_batch, &self.shape_batch);
+
+            for (i, viewport) in viewports.iter().enumerate() {
+                let Some(rect) = viewport.visible_rect(target_size) else {
+                    continue;
+                };
+
+                render_pass.set_viewport(
+                    rect.min.x,
+                    rect.min.y,
+                    rect.width(),
+                    rect.height(),
+                    0.0,
+                    1.0,
                 );
+                render_pass.set_bind_group(0, self.cameras.bind_group(i), &[]);
+
+                // only switch pipelines when the kind of geometry or the material changes,
+                // kept as whether sprites are bound and the material id
+                let mut bound = None;
+                for call in &calls {
+                    let (sprites, material) = match call {
+                        DrawCall::Sprites(batch) => (true, batch.material.as_ref()),
+                        DrawCall::Shapes(run) => (false, run.material.as_ref()),
+                    };
+                    let state = (sprites, material::material_id(material));
+                    if bound != Some(state) {
+                        match material {
+                            Some(material) => {
+                                render_pass.set_pipeline(&material.shader().pipeline());
+                                if let Some(bind_group) = material.bind_group() {
+                                    let group = material.shader().kind().material_group();
+                                    render_pass.set_bind_group(group, &*bind_group, &[]);
+                                }
+                            }
+                            None if sprites => render_pass.set_pipeline(&self.pipeline),
+                            None => render_pass.set_pipeline(&self.shape_pipeline),
+                        }
+                        if bound.map(|(sprites, _)| sprites) != Some(sprites) {
+                            if sprites {
+                                self.sprite_batch.bind(&mut render_pass);
+                            } else {
+                                self.shape_batch.bind(&mut render_pass);
+                            }
+                        }
+                        bound = Some(state);
+                    }
+
+                    match call {
+                        DrawCall::Sprites(batch) => {
+                            render_pass.set_bind_group(1, &batch.texture.bind_group, &[]);
+                            render_pass.draw_indexed(batch.indices.clone(), 0, 0..1);
+                        }
+                        DrawCall::Shapes(run) => {
+                            render_pass.draw_indexed(run.indices.clone(), 0, 0..1);
+                        }
+                    }
+                }
             }
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+    }
 
-        Ok(())
+    /// Loads an image relative to the asset root and waits for it, the file is
+    /// only read the first time and stays loaded for as long as the [`Gfx`]
+    ///
+    /// The sprite keeps the texture it was made with, draw from a [`Handle`]
+    /// out of [`Assets::load`] to see the file change while hot reloading
+    pub fn create_sprite(&self, path: &str) -> Result<mesh::Sprite, RenderError> {
+        let handle = self.assets.load::<mesh::Sprite>(path);
+        let sprite = handle.wait()?;
+
+        let mut sprites = self.sprites.lock().unwrap();
+        if !sprites.contains(&handle) {
+            sprites.push(handle);
+        }
+        Ok((*sprite).clone())
     }
 
-    pub fn create_sprite(&self, path: &str) -> mesh::Sprite {
-        mesh::Sprite::new(self, PathBuf::from_str(path).unwrap())
+    /// Loads and caches textures, fonts and other files, clone it to load from the game thread
+    pub fn assets(&self) -> &Assets {
+        &self.assets
     }
 }
 
@@ -318,4 +1070,52 @@ pub enum RenderError {
     Surface(#[from] wgpu::SurfaceError),
     #[error("could not load image from memory")]
     Image(#[from] image::ImageError),
+    #[error("could not load font: {0}")]
+    Font(&'static str),
+    #[error("invalid bitmap font: {0}")]
+    BitmapFont(String),
+    #[error("invalid aseprite file: {0}")]
+    Aseprite(String),
+    #[error("invalid shader: {0}")]
+    Shader(String),
+    #[error("could not load {}", .0.display())]
+    Asset(PathBuf, #[source] Arc<RenderError>),
+    #[error("invalid mip levels: {0}")]
+    MipLevels(String),
+    #[error("{0}x{1} textures are not supported, the largest is {2}x{2}")]
+    TextureSize(u32, u32, u32),
+    #[error("image {0} is larger than an atlas page")]
+    AtlasTooSmall(String),
+    #[error("could not read file")]
+    Io(#[from] std::io::Error),
+    #[error("panicked while loading: {0}")]
+    Panicked(String),
+    #[error("frames can only be read back from a headless renderer")]
+    NotHeadless,
+    #[error("no frame has been rendered yet")]
+    NoFrame,
+    #[error("the window can't be read back on this device")]
+    NoReadback,
+    #[error("could not map readback buffer")]
+    Readback(#[from] wgpu::BufferAsyncError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screenshot_is_the_last_frame() {
+        let mut gfx = headless((4, 4)).unwrap();
+        let mut frame = Frame::new();
+
+        frame.clear_color = Some(Color::new(1.0, 0.0, 0.0));
+        gfx.render(&frame).unwrap();
+        frame.clear_color = Some(Color::new(0.0, 0.0, 1.0));
+        gfx.render(&frame).unwrap();
+
+        let image = gfx.screenshot().unwrap();
+        assert_eq!(image.dimensions(), (4, 4));
+        assert!(image.pixels().all(|pixel| pixel.0 == [0, 0, 255, 255]));
+    }
 }
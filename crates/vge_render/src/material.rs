@@ -0,0 +1,354 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use bytemuck::Pod;
+
+use crate::{
+    Gfx, RenderError,
+    mesh::{Sprite, TexturedQuad},
+    sampler::Sampler,
+};
+
+/// 0 is left for drawing without a material
+static NEXT_MATERIAL_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Uniform buffers get padded to this, the largest alignment a WGSL struct can have
+const UNIFORM_ALIGNMENT: u64 = 16;
+
+/// What a [`Shader`] draws, decides its vertex input and the bind groups before the material's own
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderKind {
+    /// Sprites with [`DrawSprite::material`](crate::mesh::DrawSprite::material),
+    /// same vertices as `textured.wgsl`, the camera is in group 0 and the sprite
+    /// texture in group 1
+    Sprite,
+    /// Shapes painted after [`Painter::material`](crate::Painter::material),
+    /// same vertices as `colored.wgsl`, the camera is in group 0
+    Shape,
+    /// Full-screen passes of a [`PostEffect`](crate::PostEffect), only `fs_main` is
+    /// needed since `post.wgsl` gets put in front with the vertex shader, helpers
+    /// like `sample_source` and the textures being processed in group 0
+    PostProcess,
+}
+
+impl ShaderKind {
+    /// Bind group the material bindings are in
+    pub fn material_group(self) -> u32 {
+        match self {
+            Self::Sprite => 2,
+            Self::Shape | Self::PostProcess => 1,
+        }
+    }
+}
+
+/// Resource a [`Shader`] takes in its material group, numbered
+/// from binding 0 in the order they're declared
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderBinding {
+    /// `var<uniform>` of this many bytes, seen by both stages
+    Uniform(u64),
+    /// `texture_2d<f32>` with its `sampler` on the binding after it
+    Texture,
+}
+
+pub(crate) struct ShaderInner {
+    pub(crate) kind: ShaderKind,
+    bindings: Vec<ShaderBinding>,
+    /// `None` without any bindings
+    pub(crate) layout: Option<wgpu::BindGroupLayout>,
+    pub(crate) pipeline: RwLock<Arc<wgpu::RenderPipeline>>,
+    /// File the source came from, watched while hot reloading
+    pub(crate) path: Option<PathBuf>,
+}
+
+/// WGSL for drawing sprites or shapes differently, with `vs_main`
+/// and `fs_main` entry points like the built-in shaders
+///
+/// Cheap to clone, clones share the same pipeline
+#[derive(Clone)]
+pub struct Shader {
+    pub(crate) inner: Arc<ShaderInner>,
+}
+
+impl Shader {
+    pub fn new(
+        gfx: &Gfx,
+        kind: ShaderKind,
+        source: &str,
+        bindings: &[ShaderBinding],
+    ) -> Result<Self, RenderError> {
+        Self::compile(gfx, kind, source, bindings, None)
+    }
+
+    /// Reads the WGSL from `path`, it gets recompiled when the file changes while hot reloading
+    pub fn load(
+        gfx: &Gfx,
+        kind: ShaderKind,
+        path: impl AsRef<Path>,
+        bindings: &[ShaderBinding],
+    ) -> Result<Self, RenderError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)?;
+        let shader = Self::compile(gfx, kind, &source, bindings, Some(path.to_owned()))?;
+        gfx.watch_shader(&shader);
+        Ok(shader)
+    }
+
+    fn compile(
+        gfx: &Gfx,
+        kind: ShaderKind,
+        source: &str,
+        bindings: &[ShaderBinding],
+        path: Option<PathBuf>,
+    ) -> Result<Self, RenderError> {
+        let layout = (!bindings.is_empty()).then(|| Self::create_layout(&gfx.device, bindings));
+        let label = path
+            .as_ref()
+            .map_or("Material shader".into(), |path| path.to_string_lossy());
+        let pipeline = gfx.material_pipeline(kind, &label, source, layout.as_ref())?;
+
+        Ok(Self {
+            inner: Arc::new(ShaderInner {
+                kind,
+                bindings: bindings.to_vec(),
+                layout,
+                pipeline: RwLock::new(Arc::new(pipeline)),
+                path,
+            }),
+        })
+    }
+
+    fn create_layout(device: &wgpu::Device, bindings: &[ShaderBinding]) -> wgpu::BindGroupLayout {
+        let visibility = wgpu::ShaderStages::VERTEX_FRAGMENT;
+        let mut entries = Vec::new();
+        for binding in bindings {
+            let binding_types: &[wgpu::BindingType] = match binding {
+                ShaderBinding::Uniform(_) => &[wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                }],
+                ShaderBinding::Texture => &[
+                    wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                ],
+            };
+            for ty in binding_types {
+                entries.push(wgpu::BindGroupLayoutEntry {
+                    binding: entries.len() as u32,
+                    visibility,
+                    ty: *ty,
+                    count: None,
+                });
+            }
+        }
+
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Material bind group layout"),
+            entries: &entries,
+        })
+    }
+
+    pub fn kind(&self) -> ShaderKind {
+        self.inner.kind
+    }
+
+    pub fn bindings(&self) -> &[ShaderBinding] {
+        &self.inner.bindings
+    }
+
+    pub(crate) fn pipeline(&self) -> Arc<wgpu::RenderPipeline> {
+        self.inner.pipeline.read().unwrap().clone()
+    }
+}
+
+/// Value bound to one of the [`ShaderBinding`]s of a material
+enum Resource {
+    Uniform(wgpu::Buffer),
+    Texture(Arc<TexturedQuad>),
+}
+
+struct MaterialInner {
+    id: u64,
+    shader: Shader,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    resources: RwLock<Vec<Resource>>,
+    /// Rebuilt whenever a texture changes, `None` for shaders without bindings
+    bind_group: RwLock<Option<Arc<wgpu::BindGroup>>>,
+}
+
+/// [`Shader`] together with the values of its bindings, e.g. how far
+/// along a dissolve is or the color of an outline
+///
+/// Cheap to clone, clones share their values, so make one per sprite
+/// that needs its own
+#[derive(Clone)]
+pub struct Material {
+    inner: Arc<MaterialInner>,
+}
+
+impl Material {
+    /// Uniforms start out zeroed and textures plain white
+    pub fn new(gfx: &Gfx, shader: &Shader) -> Result<Self, RenderError> {
+        let mut white = None;
+        let resources = shader
+            .bindings()
+            .iter()
+            .map(|binding| {
+                Ok(match binding {
+                    ShaderBinding::Uniform(size) => Resource::Uniform(
+                        gfx.device.create_buffer(&wgpu::BufferDescriptor {
+                            label: Some("Material uniform buffer"),
+                            size: size
+                                .next_multiple_of(UNIFORM_ALIGNMENT)
+                                .max(UNIFORM_ALIGNMENT),
+                            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                            mapped_at_creation: false,
+                        }),
+                    ),
+                    ShaderBinding::Texture => {
+                        if white.is_none() {
+                            let image = image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4]));
+                            white = Some(Arc::new(TexturedQuad::from_image_sampled(
+                                &gfx.device,
+                                &gfx.queue,
+                                &gfx.texture_bind_group_layout,
+                                &image::DynamicImage::ImageRgba8(image),
+                                Sampler::nearest(),
+                                Some("Material default texture"),
+                            )?));
+                        }
+                        Resource::Texture(white.clone().unwrap())
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, RenderError>>()?;
+
+        let material = Self {
+            inner: Arc::new(MaterialInner {
+                id: NEXT_MATERIAL_ID.fetch_add(1, Ordering::Relaxed),
+                shader: shader.clone(),
+                device: gfx.device.clone(),
+                queue: gfx.queue.clone(),
+                resources: RwLock::new(resources),
+                bind_group: RwLock::new(None),
+            }),
+        };
+        material.rebuild_bind_group();
+        Ok(material)
+    }
+
+    pub fn shader(&self) -> &Shader {
+        &self.inner.shader
+    }
+
+    /// Writes `value` to the uniform declared at `index`, shows up from the next frame rendered
+    ///
+    /// Panics if there's no uniform at `index` or `value` is bigger than it
+    pub fn set_uniform<T: Pod>(&self, index: usize, value: &T) {
+        let resources = self.inner.resources.read().unwrap();
+        let Some(Resource::Uniform(buffer)) = resources.get(index) else {
+            panic!("material binding {index} is not a uniform");
+        };
+        self.inner
+            .queue
+            .write_buffer(buffer, 0, bytemuck::bytes_of(value));
+    }
+
+    /// Binds the texture of `sprite` to the texture declared at `index`,
+    /// the whole texture even if the sprite only covers part of it, sampled
+    /// with the sprite's [`Sampler`]
+    ///
+    /// Panics if there's no texture at `index`
+    pub fn set_texture(&self, index: usize, sprite: &Sprite) {
+        {
+            let mut resources = self.inner.resources.write().unwrap();
+            let Some(Resource::Texture(texture)) = resources.get_mut(index) else {
+                panic!("material binding {index} is not a texture");
+            };
+            *texture = sprite.texture.clone();
+        }
+        self.rebuild_bind_group();
+    }
+
+    fn rebuild_bind_group(&self) {
+        let Some(layout) = &self.inner.shader.inner.layout else {
+            return;
+        };
+
+        let resources = self.inner.resources.read().unwrap();
+        let views: Vec<Option<(wgpu::TextureView, wgpu::Sampler)>> = resources
+            .iter()
+            .map(|resource| match resource {
+                Resource::Texture(texture) => Some((
+                    texture
+                        .texture
+                        .create_view(&wgpu::TextureViewDescriptor::default()),
+                    texture
+                        .sampler()
+                        .create(&self.inner.device, Some("Material sampler")),
+                )),
+                Resource::Uniform(_) => None,
+            })
+            .collect();
+
+        let mut entries = Vec::new();
+        for (resource, view) in resources.iter().zip(&views) {
+            match (resource, view) {
+                (Resource::Uniform(buffer), _) => entries.push(buffer.as_entire_binding()),
+                (Resource::Texture(_), Some((view, sampler))) => {
+                    entries.push(wgpu::BindingResource::TextureView(view));
+                    entries.push(wgpu::BindingResource::Sampler(sampler));
+                }
+                (Resource::Texture(_), None) => unreachable!(),
+            }
+        }
+        let entries: Vec<wgpu::BindGroupEntry> = entries
+            .into_iter()
+            .enumerate()
+            .map(|(binding, resource)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource,
+            })
+            .collect();
+
+        let bind_group = self
+            .inner
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Material bind group"),
+                layout,
+                entries: &entries,
+            });
+        *self.inner.bind_group.write().unwrap() = Some(Arc::new(bind_group));
+    }
+
+    pub(crate) fn bind_group(&self) -> Option<Arc<wgpu::BindGroup>> {
+        self.inner.bind_group.read().unwrap().clone()
+    }
+}
+
+impl fmt::Debug for Material {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Material")
+            .field("id", &self.inner.id)
+            .field("kind", &self.inner.shader.kind())
+            .finish()
+    }
+}
+
+/// Keeps draws with the same material together, 0 without one
+pub(crate) fn material_id(material: Option<&Material>) -> u64 {
+    material.map_or(0, |material| material.inner.id)
+}
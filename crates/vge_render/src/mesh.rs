@@ -1,58 +1,185 @@
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 use vge_math::{Rect, Vec2};
-use wgpu::util::DeviceExt;
 
 use crate::{
-    Gfx, RenderError,
-    primitives::{Primitive, Quad, VertexTextured},
+    Color, Gfx, RenderError,
+    aseprite::{Aseprite, is_aseprite},
+    material::Material,
+    mipmap,
+    sampler::Sampler,
+    text::{self, Align, Font, TextLayout},
 };
 
+static NEXT_TEXTURE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// wgpu panics on empty textures and ones past the device limits instead of erroring
+pub(crate) fn check_texture_size(
+    device: &wgpu::Device,
+    width: u32,
+    height: u32,
+) -> Result<(), RenderError> {
+    let max = device.limits().max_texture_dimension_2d;
+    if width == 0 || height == 0 || width > max || height > max {
+        return Err(RenderError::TextureSize(width, height, max));
+    }
+    Ok(())
+}
+
 pub struct TexturedQuad {
-    pub(crate) texture: wgpu::Texture,
-    pub(crate) view: wgpu::TextureView,
-    pub(crate) sampler: wgpu::Sampler,
-    pub(crate) vtx_buf: wgpu::Buffer,
-    pub(crate) idx_buf: wgpu::Buffer,
-    pub(crate) quad: Quad<VertexTextured>,
+    /// Identifies the texture when batching sprites
+    pub(crate) id: u64,
+    pub(crate) texture: Arc<wgpu::Texture>,
+    sampler: Sampler,
+    /// Texture view and sampler bound together for the textured pipeline
+    pub(crate) bind_group: wgpu::BindGroup,
 }
 
 impl TexturedQuad {
+    /// Sampled with [`Gfx::default_sampler`], with mipmaps if [`Gfx::mipmaps`] is on
     pub fn new(gfx: &Gfx<'_>, bytes: &[u8], label: &str) -> Result<Self, RenderError> {
-        Self::from_bytes(&gfx.device, &gfx.queue, bytes, label)
+        Self::from_bytes_sampled(
+            &gfx.device,
+            &gfx.queue,
+            &gfx.texture_bind_group_layout,
+            bytes,
+            gfx.default_sampler(),
+            gfx.mipmaps(),
+            label,
+        )
     }
 
     pub fn from_bytes(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
         bytes: &[u8],
         label: &str,
     ) -> Result<Self, RenderError> {
-        let img = image::load_from_memory(bytes)?;
-        Self::from_image(device, queue, &img, Some(label))
+        Self::from_bytes_sampled(
+            device,
+            queue,
+            layout,
+            bytes,
+            Sampler::default(),
+            false,
+            label,
+        )
+    }
+
+    pub(crate) fn from_bytes_sampled(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        bytes: &[u8],
+        sampler: Sampler,
+        mipmaps: bool,
+        label: &str,
+    ) -> Result<Self, RenderError> {
+        let img = if is_aseprite(bytes) {
+            // first frame with every visible layer flattened
+            let frame = Aseprite::from_bytes(bytes)?.frames.swap_remove(0);
+            image::DynamicImage::ImageRgba8(frame.image)
+        } else {
+            image::load_from_memory(bytes)?
+        };
+        if mipmaps {
+            Self::from_image_mipmapped(device, queue, layout, &img, sampler, Some(label))
+        } else {
+            Self::from_image_sampled(device, queue, layout, &img, sampler, Some(label))
+        }
     }
 
     pub fn from_image(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
         img: &image::DynamicImage,
         label: Option<&str>,
     ) -> Result<Self, RenderError> {
-        let image = img;
-        let rgba = image.to_rgba8();
+        Self::from_image_sampled(device, queue, layout, img, Sampler::default(), label)
+    }
 
-        let dimensions = rgba.dimensions();
+    /// Same as [`TexturedQuad::from_image`] with a different [`Sampler`]
+    pub fn from_image_sampled(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        img: &image::DynamicImage,
+        sampler: Sampler,
+        label: Option<&str>,
+    ) -> Result<Self, RenderError> {
+        Self::from_mip_levels(device, queue, layout, &[img.to_rgba8()], sampler, label)
+    }
+
+    /// Same as [`TexturedQuad::from_image_sampled`] with a full mip chain
+    /// generated from `img`, so it doesn't shimmer when drawn smaller
+    ///
+    /// Levels are only blended with [`Filter::Linear`](crate::Filter::Linear)
+    /// as the sampler's `mipmap_filter`, e.g. [`Sampler::linear`]
+    pub fn from_image_mipmapped(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        img: &image::DynamicImage,
+        sampler: Sampler,
+        label: Option<&str>,
+    ) -> Result<Self, RenderError> {
+        check_texture_size(device, img.width(), img.height())?;
+        let levels = mipmap::generate(&img.to_rgba8());
+        Self::from_mip_levels(device, queue, layout, &levels, sampler, label)
+    }
 
-        let texture_size = wgpu::Extent3d {
-            width: dimensions.0,
-            height: dimensions.1,
-            depth_or_array_layers: 1,
+    /// Uploads mip levels made ahead of time, starting with the full size image,
+    /// every level has to be half the size of the one before rounded down
+    ///
+    /// The chain can stop before it reaches 1x1
+    pub fn from_mip_levels(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        levels: &[image::RgbaImage],
+        sampler: Sampler,
+        label: Option<&str>,
+    ) -> Result<Self, RenderError> {
+        let Some(base) = levels.first() else {
+            return Err(RenderError::MipLevels("no levels given".into()));
         };
+        let (width, height) = base.dimensions();
+        check_texture_size(device, width, height)?;
+        if levels.len() as u32 > mipmap::level_count(width, height) {
+            return Err(RenderError::MipLevels(format!(
+                "{} levels is more than a {width}x{height} texture can have",
+                levels.len()
+            )));
+        }
+        for (level, image) in levels.iter().enumerate() {
+            let expected = mipmap::level_size(width, height, level as u32);
+            if image.dimensions() != expected {
+                return Err(RenderError::MipLevels(format!(
+                    "level {level} is {}x{}, expected {}x{}",
+                    image.width(),
+                    image.height(),
+                    expected.0,
+                    expected.1
+                )));
+            }
+        }
 
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label,
-            size: texture_size,
-            mip_level_count: 1,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: levels.len() as u32,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -60,79 +187,338 @@ impl TexturedQuad {
             view_formats: &[],
         });
 
-        queue.write_texture(
-            wgpu::ImageCopyTextureBase {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &rgba,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: Some(dimensions.1),
-            },
-            texture_size,
-        );
+        for (level, image) in levels.iter().enumerate() {
+            let (width, height) = image.dimensions();
+            queue.write_texture(
+                wgpu::ImageCopyTextureBase {
+                    texture: &texture,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                image,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * width),
+                    rows_per_image: Some(height),
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
 
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Ok(Self::from_texture_sampled(
+            device, layout, texture, sampler, label,
+        ))
+    }
 
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("Sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+    /// Wraps a texture that was already created and filled in, it has to be
+    /// usable as [`wgpu::TextureUsages::TEXTURE_BINDING`]
+    pub fn from_texture(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        texture: wgpu::Texture,
+        label: Option<&str>,
+    ) -> Self {
+        Self::from_texture_sampled(device, layout, texture, Sampler::default(), label)
+    }
 
-        let quad = Quad::textured(Rect::new(Vec2::new(-0.5, -0.5), Vec2::new(0.5, 0.5)));
+    /// Same as [`TexturedQuad::from_texture`] with a different [`Sampler`]
+    pub fn from_texture_sampled(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        texture: wgpu::Texture,
+        sampler: Sampler,
+        label: Option<&str>,
+    ) -> Self {
+        Self::bind(device, layout, Arc::new(texture), sampler, label)
+    }
 
-        let vtx_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex bufer"),
-            contents: bytemuck::bytes_of(&quad.vertices()),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
+    /// The same texture sampled differently, the texture itself isn't copied
+    pub fn with_sampler(
+        &self,
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: Sampler,
+    ) -> Self {
+        Self::bind(device, layout, self.texture.clone(), sampler, None)
+    }
 
-        let idx_buf = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(quad.indices().unwrap()),
-            usage: wgpu::BufferUsages::INDEX,
+    fn bind(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        texture: Arc<wgpu::Texture>,
+        sampler: Sampler,
+        label: Option<&str>,
+    ) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let wgpu_sampler = sampler.create(device, Some("Sampler"));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label,
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&wgpu_sampler),
+                },
+            ],
         });
 
-        Ok(Self {
+        Self {
+            id: NEXT_TEXTURE_ID.fetch_add(1, Ordering::Relaxed),
             texture,
-            view,
             sampler,
-            vtx_buf,
-            idx_buf,
-            quad,
-        })
+            bind_group,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    pub fn sampler(&self) -> Sampler {
+        self.sampler
+    }
+
+    /// 1 without mipmaps
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
     }
 }
 
+/// Cheap to clone, clones share the same texture
+#[derive(Clone)]
 pub struct Sprite {
-    pub(crate) texture: TexturedQuad,
+    pub(crate) texture: Arc<TexturedQuad>,
+    /// Part of the texture the sprite covers in normalized coordinates,
+    /// all of it unless the sprite comes from an atlas
+    pub(crate) region: Rect,
 }
 
 impl Sprite {
-    pub fn new(gfx: &Gfx, path: PathBuf) -> Self {
-        let bytes = std::fs::read(path).unwrap();
+    pub fn new(gfx: &Gfx, path: PathBuf) -> Result<Self, RenderError> {
+        let bytes = std::fs::read(path)?;
+        let texture = TexturedQuad::new(gfx, &bytes, "Sprite")?;
+        Ok(Self::from_texture(Arc::new(texture)))
+    }
+
+    /// Same sprite drawn with a different [`Sampler`], e.g. [`Sampler::nearest`]
+    /// for pixel art or repeating for tiled backgrounds
+    ///
+    /// Shares the texture but no longer batches with sprites sampled the old way
+    pub fn with_sampler(&self, gfx: &Gfx, sampler: Sampler) -> Sprite {
+        let texture =
+            self.texture
+                .with_sampler(&gfx.device, &gfx.texture_bind_group_layout, sampler);
+        Self::with_region(Arc::new(texture), self.region)
+    }
+
+    pub fn sampler(&self) -> Sampler {
+        self.texture.sampler()
+    }
+
+    pub(crate) fn from_texture(texture: Arc<TexturedQuad>) -> Self {
+        Self::with_region(texture, Rect::new(Vec2::ZERO, Vec2::splat(1.0)))
+    }
+
+    pub(crate) fn with_region(texture: Arc<TexturedQuad>, region: Rect) -> Self {
+        Self { texture, region }
+    }
+
+    /// In texels
+    pub fn size(&self) -> (u32, u32) {
+        let (width, height) = self.texture.size();
+        (
+            (width as f32 * self.region.width()).round() as u32,
+            (height as f32 * self.region.height()).round() as u32,
+        )
+    }
+
+    /// Part of this sprite as its own sprite, `rect` is in texels with (0, 0) at the top left,
+    /// sprites cut from the same texture still batch together
+    pub fn region(&self, rect: Rect) -> Sprite {
+        let (width, height) = self.size();
+        let size = Vec2::new(width as f32, height as f32);
+        let uv = Rect::new(rect.min / size, rect.max / size);
+        Self::with_region(self.texture.clone(), self.texture_uv(uv))
+    }
+
+    /// Maps `uv` from the sprite to the whole texture
+    pub(crate) fn texture_uv(&self, uv: Rect) -> Rect {
+        let size = self.region.size();
+        Rect::new(
+            self.region.min + uv.min * size,
+            self.region.min + uv.max * size,
+        )
+    }
+
+    pub fn at(&self, position: Vec2) -> DrawSprite {
+        DrawSprite::new(self.clone(), position)
+    }
+}
+
+/// A single sprite draw within a frame
+#[derive(Clone)]
+pub struct DrawSprite {
+    pub sprite: Sprite,
+    pub position: Vec2,
+    pub scale: Vec2,
+    /// Counter-clockwise, in radians
+    pub rotation: f32,
+    /// Point the sprite is positioned and rotated around,
+    /// (0, 0) is the bottom left and (1, 1) the top right
+    pub origin: Vec2,
+    pub tint: Color,
+    /// Part of the sprite to draw in normalized coordinates,
+    /// (0, 0) is the top left of the sprite
+    pub uv: Rect,
+    /// Higher layers are drawn on top, draw order between
+    /// different textures on the same layer is unspecified
+    pub layer: i32,
+    /// Draws with this instead of the built-in shader, ignored unless
+    /// it's a [`ShaderKind::Sprite`](crate::ShaderKind::Sprite) material
+    pub material: Option<Material>,
+}
+
+impl DrawSprite {
+    pub fn new(sprite: Sprite, position: Vec2) -> Self {
         Self {
-            texture: TexturedQuad::new(gfx, &bytes, "Sprite").unwrap(),
+            sprite,
+            position,
+            scale: Vec2::splat(1.0),
+            rotation: 0.0,
+            origin: Vec2::splat(0.5),
+            tint: Color::WHITE,
+            uv: Rect::new(Vec2::ZERO, Vec2::splat(1.0)),
+            layer: 0,
+            material: None,
         }
     }
+
+    pub fn scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn origin(mut self, origin: Vec2) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn uv(mut self, uv: Rect) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    pub fn layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn material(mut self, material: Material) -> Self {
+        self.material = Some(material);
+        self
+    }
 }
-// TODO: Make meshes work
+/// Text drawn with a [`Font`], laid out and rasterized when the frame is rendered
+#[derive(Clone)]
 pub struct Text {
-    text: String,
+    pub text: String,
+    pub font: Font,
+    pub position: Vec2,
+    /// Font size in pixels, see [`Font::default_size`]
+    pub size: f32,
+    pub color: Color,
+    /// Lines wrap at word boundaries when they'd get wider than this
+    pub max_width: Option<f32>,
+    /// Alignment within `max_width`, or within the widest line without one
+    pub align: Align,
+    /// Multiplies the line spacing of the font
+    pub line_height: f32,
+    /// Point of the text box that goes at the position,
+    /// (0, 0) is the bottom left and (1, 1) the top right
+    pub origin: Vec2,
+    /// Same as [`DrawSprite::layer`], text isn't ordered against sprites on the
+    /// same layer, so put it above the sprites it should show up over
+    pub layer: i32,
 }
 
 impl Text {
-    pub fn new(text: impl Into<String>) -> Self {
-        Self { text: text.into() }
+    pub fn new(font: Font, text: impl Into<String>, position: Vec2) -> Self {
+        Self {
+            text: text.into(),
+            size: font.default_size(),
+            font,
+            position,
+            color: Color::WHITE,
+            max_width: None,
+            align: Align::Left,
+            line_height: 1.0,
+            origin: Vec2::new(0.0, 1.0),
+            layer: 0,
+        }
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    pub fn origin(mut self, origin: Vec2) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Wraps and aligns the text, glyphs are placed relative to the position
+    pub fn layout(&self) -> TextLayout {
+        text::layout(self)
+    }
+
+    /// Size of the text box after wrapping
+    pub fn measure(&self) -> Vec2 {
+        self.layout().bounds.size()
     }
 }
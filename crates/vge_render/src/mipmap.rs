@@ -0,0 +1,158 @@
+use image::RgbaImage;
+
+/// Number of levels in a full mip chain for a texture of `width` by `height`, down to 1x1
+pub fn level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Size of mip `level` of a texture of `width` by `height`
+pub fn level_size(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+/// Full mip chain starting with `image` itself, every level half the size of the one before
+///
+/// Averages 2x2 blocks, or 3x3 with partial weights along odd sizes, in linear space
+/// with premultiplied alpha, so edges of transparent sprites don't darken as they shrink
+pub fn generate(image: &RgbaImage) -> Vec<RgbaImage> {
+    let (width, height) = image.dimensions();
+    let mut levels = vec![image.clone()];
+    for level in 1..level_count(width, height) {
+        let next = downsample(levels.last().unwrap(), level_size(width, height, level));
+        levels.push(next);
+    }
+    levels
+}
+
+fn downsample(image: &RgbaImage, (width, height): (u32, u32)) -> RgbaImage {
+    let (src_width, src_height) = image.dimensions();
+    let columns: Vec<_> = (0..width).map(|x| taps(src_width, width, x)).collect();
+    let rows: Vec<_> = (0..height).map(|y| taps(src_height, height, y)).collect();
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let mut sum = [0.0f32; 4];
+        for &(sy, y_weight) in &rows[y as usize] {
+            for &(sx, x_weight) in &columns[x as usize] {
+                let [r, g, b, a] = image.get_pixel(sx, sy).0;
+                let alpha = a as f32 / 255.0 * x_weight * y_weight;
+                sum[0] += srgb_to_linear(r) * alpha;
+                sum[1] += srgb_to_linear(g) * alpha;
+                sum[2] += srgb_to_linear(b) * alpha;
+                sum[3] += alpha;
+            }
+        }
+
+        let alpha = sum[3];
+        if alpha <= 0.0 {
+            return image::Rgba([0; 4]);
+        }
+        let color = |premultiplied: f32| linear_to_srgb(premultiplied / alpha);
+        image::Rgba([
+            color(sum[0]),
+            color(sum[1]),
+            color(sum[2]),
+            (alpha * 255.0).round() as u8,
+        ])
+    })
+}
+
+/// Source texels covered by texel `index` of a row shrunk from `src` to `dst` texels,
+/// with how much of it each one covers. Two for even sizes, odd sizes take three
+/// so the last row or column isn't dropped
+fn taps(src: u32, dst: u32, index: u32) -> Vec<(u32, f32)> {
+    let scale = src as f32 / dst as f32;
+    let start = index as f32 * scale;
+    let end = start + scale;
+    (start.floor() as u32..(end.ceil() as u32).min(src))
+        .map(|texel| {
+            let covered = end.min(texel as f32 + 1.0) - start.max(texel as f32);
+            (texel, covered / scale)
+        })
+        .filter(|(_, weight)| *weight > 0.0)
+        .collect()
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let srgb = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn levels() {
+        assert_eq!(level_count(1, 1), 1);
+        assert_eq!(level_count(0, 0), 1);
+        assert_eq!(level_count(2, 1), 2);
+        assert_eq!(level_count(256, 256), 9);
+        assert_eq!(level_count(257, 16), 9);
+        assert_eq!(level_count(5, 300), 9);
+
+        assert_eq!(level_size(256, 64, 0), (256, 64));
+        assert_eq!(level_size(256, 64, 3), (32, 8));
+        assert_eq!(level_size(256, 64, 7), (2, 1));
+        assert_eq!(level_size(5, 3, 1), (2, 1));
+        assert_eq!(level_size(5, 3, 2), (1, 1));
+
+        let image = RgbaImage::new(37, 10);
+        let chain = generate(&image);
+        assert_eq!(chain.len(), level_count(37, 10) as usize);
+        for (level, image) in chain.iter().enumerate() {
+            assert_eq!(image.dimensions(), level_size(37, 10, level as u32));
+        }
+    }
+
+    #[test]
+    fn premultiplied_average() {
+        let image = RgbaImage::from_vec(
+            2,
+            2,
+            vec![
+                255, 0, 0, 255, //
+                0, 0, 255, 255, //
+                0, 255, 0, 0, //
+                0, 255, 0, 0,
+            ],
+        )
+        .unwrap();
+        let level = &generate(&image)[1];
+
+        // the transparent green doesn't bleed in, red and blue mix in linear space
+        let half = linear_to_srgb(0.5);
+        assert_eq!(half, 188);
+        assert_eq!(level.get_pixel(0, 0), &Rgba([half, 0, half, 128]));
+    }
+
+    #[test]
+    fn odd_sizes_keep_the_edges() {
+        let mut image = RgbaImage::from_pixel(5, 1, Rgba([0, 0, 0, 255]));
+        image.put_pixel(4, 0, Rgba([255, 255, 255, 255]));
+
+        assert_eq!(taps(5, 2, 0), [(0, 0.4), (1, 0.4), (2, 0.2)]);
+        assert_eq!(taps(5, 2, 1), [(2, 0.2), (3, 0.4), (4, 0.4)]);
+        assert_eq!(taps(4, 2, 1), [(2, 0.5), (3, 0.5)]);
+
+        let level = &generate(&image)[1];
+        assert_eq!(level.dimensions(), (2, 1));
+        assert_eq!(level.get_pixel(0, 0), &Rgba([0, 0, 0, 255]));
+        let white = linear_to_srgb(0.4);
+        assert_eq!(level.get_pixel(1, 0), &Rgba([white, white, white, 255]));
+    }
+}
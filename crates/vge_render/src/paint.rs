@@ -0,0 +1,351 @@
+use std::{f32::consts::TAU, ops::Range};
+
+use vge_math::{Rect, Shape, Vec2};
+
+use crate::{
+    material::{Material, material_id},
+    primitives::{Color, VertexColored},
+};
+
+/// Miter joins longer than this many half thicknesses get clamped, keeps sharp corners from spiking
+const MITER_LIMIT: f32 = 4.0;
+
+/// Tessellates shapes into colored triangles, drawn through the colored pipeline
+///
+/// Outlines are centered on the edge of the shape, and filled polygons have to be convex
+#[derive(Clone, Debug, Default)]
+pub struct Painter {
+    vertices: Vec<VertexColored>,
+    indices: Vec<u32>,
+    /// In painting order
+    runs: Vec<Run>,
+    layer: i32,
+    material: Option<Material>,
+}
+
+/// Index range of shapes painted one after another with the same layer and material
+#[derive(Clone, Debug)]
+pub(crate) struct Run {
+    pub(crate) layer: i32,
+    pub(crate) material: Option<Material>,
+    pub(crate) indices: Range<u32>,
+}
+
+impl Painter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Layer for everything painted afterwards, shapes draw on top of sprites on the same layer
+    pub fn layer(&mut self, layer: i32) -> &mut Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Material for everything painted afterwards, `None` goes back to plain colors,
+    /// ignored unless it's a [`ShaderKind::Shape`](crate::ShaderKind::Shape) material
+    pub fn material(&mut self, material: Option<Material>) -> &mut Self {
+        self.material = material;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.runs.clear();
+    }
+
+    /// Moves everything painted on `other` on top of this painter
+    pub fn append(&mut self, other: Painter) {
+        let vertex_offset = self.vertices.len() as u32;
+        let index_offset = self.indices.len() as u32;
+
+        self.vertices.extend(other.vertices);
+        self.indices
+            .extend(other.indices.into_iter().map(|i| i + vertex_offset));
+        for run in other.runs {
+            self.push_run(Run {
+                indices: run.indices.start + index_offset..run.indices.end + index_offset,
+                ..run
+            });
+        }
+    }
+
+    pub(crate) fn vertices(&self) -> &[VertexColored] {
+        &self.vertices
+    }
+
+    pub(crate) fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    pub(crate) fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    pub fn triangle(&mut self, a: Vec2, b: Vec2, c: Vec2, color: Color) -> &mut Self {
+        self.polygon(&[a, b, c], color)
+    }
+
+    pub fn rect(&mut self, rect: Rect, color: Color) -> &mut Self {
+        self.polygon(&rect.corners(), color)
+    }
+
+    pub fn rect_outline(&mut self, rect: Rect, thickness: f32, color: Color) -> &mut Self {
+        self.polygon_outline(&rect.corners(), thickness, color)
+    }
+
+    /// `radius` is clamped to half the shorter side
+    pub fn rounded_rect(&mut self, rect: Rect, radius: f32, color: Color) -> &mut Self {
+        let points = Self::rounded_rect_points(rect, radius);
+        self.polygon(&points, color)
+    }
+
+    pub fn rounded_rect_outline(
+        &mut self,
+        rect: Rect,
+        radius: f32,
+        thickness: f32,
+        color: Color,
+    ) -> &mut Self {
+        let points = Self::rounded_rect_points(rect, radius);
+        self.polygon_outline(&points, thickness, color)
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: Color) -> &mut Self {
+        self.ellipse(center, Vec2::splat(radius), color)
+    }
+
+    pub fn circle_outline(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        thickness: f32,
+        color: Color,
+    ) -> &mut Self {
+        self.ellipse_outline(center, Vec2::splat(radius), thickness, color)
+    }
+
+    pub fn ellipse(&mut self, center: Vec2, radii: Vec2, color: Color) -> &mut Self {
+        let points = Self::arc_points(center, radii, 0.0, TAU, false);
+        self.polygon(&points, color)
+    }
+
+    pub fn ellipse_outline(
+        &mut self,
+        center: Vec2,
+        radii: Vec2,
+        thickness: f32,
+        color: Color,
+    ) -> &mut Self {
+        let points = Self::arc_points(center, radii, 0.0, TAU, false);
+        self.polygon_outline(&points, thickness, color)
+    }
+
+    /// Outline of a circle going counter-clockwise from `start` to `end`, in radians
+    pub fn arc(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        start: f32,
+        end: f32,
+        thickness: f32,
+        color: Color,
+    ) -> &mut Self {
+        let points = Self::arc_points(center, Vec2::splat(radius), start, end, true);
+        self.polyline(&points, thickness, color)
+    }
+
+    /// Filled slice of a circle going counter-clockwise from `start` to `end`, in radians
+    pub fn pie(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        start: f32,
+        end: f32,
+        color: Color,
+    ) -> &mut Self {
+        let mut points = vec![center];
+        points.extend(Self::arc_points(
+            center,
+            Vec2::splat(radius),
+            start,
+            end,
+            true,
+        ));
+        self.polygon(&points, color)
+    }
+
+    pub fn line(&mut self, start: Vec2, end: Vec2, thickness: f32, color: Color) -> &mut Self {
+        self.polyline(&[start, end], thickness, color)
+    }
+
+    /// Connected lines with mitered joins, the ends are left open
+    pub fn polyline(&mut self, points: &[Vec2], thickness: f32, color: Color) -> &mut Self {
+        self.stroke(points, false, thickness, color);
+        self
+    }
+
+    /// Filled convex polygon, the points may be wound either way
+    pub fn polygon(&mut self, points: &[Vec2], color: Color) -> &mut Self {
+        if points.len() < 3 {
+            return self;
+        }
+
+        let start = self.vertices.len() as u32;
+        self.vertices.extend(
+            points
+                .iter()
+                .map(|point| VertexColored::new(point.extend(0.0), color)),
+        );
+
+        let indices = (1..points.len() as u32 - 1).flat_map(|i| [start, start + i, start + i + 1]);
+        self.push_indices(indices);
+        self
+    }
+
+    /// Closed outline through `points`
+    pub fn polygon_outline(&mut self, points: &[Vec2], thickness: f32, color: Color) -> &mut Self {
+        self.stroke(points, true, thickness, color);
+        self
+    }
+
+    /// Handy for seeing what collision is doing
+    pub fn shape(&mut self, shape: &Shape, color: Color) -> &mut Self {
+        match shape {
+            Shape::Circle(circle) => self.circle(circle.center, circle.radius, color),
+            Shape::Rect(rect) => self.rect(*rect, color),
+            Shape::Polygon(polygon) => self.polygon(polygon.points(), color),
+        }
+    }
+
+    pub fn shape_outline(&mut self, shape: &Shape, thickness: f32, color: Color) -> &mut Self {
+        match shape {
+            Shape::Circle(circle) => {
+                self.circle_outline(circle.center, circle.radius, thickness, color)
+            }
+            Shape::Rect(rect) => self.rect_outline(*rect, thickness, color),
+            Shape::Polygon(polygon) => self.polygon_outline(polygon.points(), thickness, color),
+        }
+    }
+
+    fn stroke(&mut self, points: &[Vec2], closed: bool, thickness: f32, color: Color) {
+        let mut points = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        if points.len() < 2 {
+            return;
+        }
+
+        let half = thickness * 0.5;
+        let count = points.len();
+        let start = self.vertices.len() as u32;
+
+        for i in 0..count {
+            let prev = (closed || i > 0).then(|| points[(i + count - 1) % count]);
+            let next = (closed || i + 1 < count).then(|| points[(i + 1) % count]);
+            let point = points[i];
+
+            let normal_in = prev.map(|prev| (point - prev).perp().normalize_or_zero());
+            let normal_out = next.map(|next| (next - point).perp().normalize_or_zero());
+            let offset = match (normal_in, normal_out) {
+                (Some(a), Some(b)) => {
+                    let miter = (a + b).normalize_or(b);
+                    let scale = half / miter.dot(b).max(1.0 / MITER_LIMIT);
+                    miter * scale
+                }
+                (Some(normal), None) | (None, Some(normal)) => normal * half,
+                (None, None) => Vec2::ZERO,
+            };
+
+            self.vertices.extend([
+                VertexColored::new((point + offset).extend(0.0), color),
+                VertexColored::new((point - offset).extend(0.0), color),
+            ]);
+        }
+
+        let segments = if closed { count } else { count - 1 } as u32;
+        let count = count as u32;
+        let indices = (0..segments).flat_map(|i| {
+            let a = start + i * 2;
+            let b = start + (i + 1) % count * 2;
+            [a, a + 1, b, b, a + 1, b + 1]
+        });
+        self.push_indices(indices);
+    }
+
+    fn push_indices(&mut self, indices: impl IntoIterator<Item = u32>) {
+        let start = self.indices.len() as u32;
+        self.indices.extend(indices);
+        let end = self.indices.len() as u32;
+        self.push_run(Run {
+            layer: self.layer,
+            material: self.material.clone(),
+            indices: start..end,
+        });
+    }
+
+    fn push_run(&mut self, run: Run) {
+        match self.runs.last_mut() {
+            Some(last)
+                if last.layer == run.layer
+                    && material_id(last.material.as_ref())
+                        == material_id(run.material.as_ref())
+                    && last.indices.end == run.indices.start =>
+            {
+                last.indices.end = run.indices.end
+            }
+            _ => self.runs.push(run),
+        }
+    }
+
+    /// Enough segments for a curve of `radius` world units to look round at zoom 1
+    fn segments(radius: f32, angle: f32) -> usize {
+        let full = (radius.abs().sqrt() * 4.0).clamp(12.0, 128.0);
+        ((full * angle.abs() / TAU).ceil() as usize).max(1)
+    }
+
+    /// Points along an elliptical arc, `inclusive` also adds the end point
+    fn arc_points(center: Vec2, radii: Vec2, start: f32, end: f32, inclusive: bool) -> Vec<Vec2> {
+        let sweep = end - start;
+        let segments = Self::segments(radii.max_element(), sweep);
+        let last = if inclusive { segments + 1 } else { segments };
+        (0..last)
+            .map(|i| {
+                let angle = start + sweep * i as f32 / segments as f32;
+                center + Vec2::from_angle(angle) * radii
+            })
+            .collect()
+    }
+
+    fn rounded_rect_points(rect: Rect, radius: f32) -> Vec<Vec2> {
+        // rects can have a negative size, flipping them keeps the outline counter-clockwise
+        let rect = Rect::from_corners(rect.min, rect.max);
+        let radius = radius.min(rect.size().min_element() * 0.5).max(0.0);
+        if radius <= 0.0 {
+            return rect.corners().to_vec();
+        }
+
+        let inner = rect.expand(-radius);
+        let quarter = TAU / 4.0;
+        let corners = [
+            (Vec2::new(inner.max.x, inner.min.y), -quarter),
+            (inner.max, 0.0),
+            (Vec2::new(inner.min.x, inner.max.y), quarter),
+            (inner.min, 2.0 * quarter),
+        ];
+
+        corners
+            .into_iter()
+            .flat_map(|(center, start)| {
+                Self::arc_points(center, Vec2::splat(radius), start, start + quarter, true)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    sync::{Arc, Mutex, mpsc},
+    thread::JoinHandle,
+};
+
+enum Sender<T> {
+    Unbounded(mpsc::Sender<T>),
+    Bounded(mpsc::SyncSender<T>),
+}
+
+/// A few threads taking work off a shared queue
+///
+/// Dropping the pool lets the threads finish what's queued and stop on their own,
+/// [`WorkerPool::join`] waits for that to happen
+pub(crate) struct WorkerPool<T> {
+    sender: Option<Sender<T>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> WorkerPool<T> {
+    /// Spawns up to `max_threads` threads named `name` and a number, each running `work`
+    /// on whatever gets sent. Sending blocks once `queue_size` items are waiting,
+    /// the queue is unbounded without one
+    pub(crate) fn new(
+        name: &str,
+        max_threads: usize,
+        queue_size: Option<usize>,
+        work: impl Fn(T) + Send + Sync + 'static,
+    ) -> std::io::Result<Self> {
+        let (sender, receiver) = match queue_size {
+            Some(size) => {
+                let (sender, receiver) = mpsc::sync_channel(size);
+                (Sender::Bounded(sender), receiver)
+            }
+            None => {
+                let (sender, receiver) = mpsc::channel();
+                (Sender::Unbounded(sender), receiver)
+            }
+        };
+        let receiver = Arc::new(Mutex::new(receiver));
+        let work = Arc::new(work);
+
+        let count = std::thread::available_parallelism()
+            .map_or(1, |count| count.get())
+            .min(max_threads);
+        let mut threads = Vec::with_capacity(count);
+        for i in 0..count {
+            let receiver = receiver.clone();
+            let work = work.clone();
+            let thread = std::thread::Builder::new()
+                .name(format!("{name} {i}"))
+                .spawn(move || {
+                    // stops once the pool is gone and the queue runs dry
+                    loop {
+                        let item = receiver.lock().unwrap().recv();
+                        match item {
+                            Ok(item) => work(item),
+                            Err(_) => break,
+                        }
+                    }
+                })?;
+            threads.push(thread);
+        }
+
+        Ok(Self {
+            sender: Some(sender),
+            threads,
+        })
+    }
+
+    /// Queues `item` for the next free thread, gives it back if there are none left
+    pub(crate) fn send(&self, item: T) -> Result<(), T> {
+        let result = match &self.sender {
+            Some(Sender::Unbounded(sender)) => sender.send(item),
+            Some(Sender::Bounded(sender)) => sender.send(item),
+            None => return Err(item),
+        };
+        result.map_err(|mpsc::SendError(item)| item)
+    }
+
+    /// Stops taking work and waits for everything queued to be done
+    pub(crate) fn join(&mut self) {
+        self.sender = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
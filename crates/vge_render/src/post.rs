@@ -0,0 +1,499 @@
+use std::fmt;
+
+use bytemuck::{Pod, Zeroable};
+use vge_math::{Rect, Vec2};
+
+use crate::{
+    Gfx, RenderError,
+    camera::Viewport,
+    material::{Material, Shader, ShaderBinding, ShaderKind},
+    mesh::Sprite,
+    sampler::Sampler,
+    target::RenderTarget,
+};
+
+/// Put in front of every [`ShaderKind::PostProcess`] shader
+pub(crate) const POST_PRELUDE: &str = include_str!("../../../assets/shaders/post.wgsl");
+
+const VIGNETTE_SHADER: &str = include_str!("../../../assets/shaders/post/vignette.wgsl");
+const CRT_SHADER: &str = include_str!("../../../assets/shaders/post/crt.wgsl");
+const CHROMATIC_ABERRATION_SHADER: &str =
+    include_str!("../../../assets/shaders/post/chromatic_aberration.wgsl");
+const COLOR_GRADE_SHADER: &str = include_str!("../../../assets/shaders/post/color_grade.wgsl");
+const BLUR_SHADER: &str = include_str!("../../../assets/shaders/post/blur.wgsl");
+const BLOOM_THRESHOLD_SHADER: &str =
+    include_str!("../../../assets/shaders/post/bloom_threshold.wgsl");
+const BLOOM_COMBINE_SHADER: &str = include_str!("../../../assets/shaders/post/bloom_combine.wgsl");
+
+/// The built-in effects take their parameters as one of these
+const PARAMS: &[ShaderBinding] = &[ShaderBinding::Uniform(16)];
+
+/// Full-screen passes run over what a [`Viewport`] drew, after the whole
+/// frame is drawn, one after the other
+///
+/// Every pass is a [`Material`] of a [`ShaderKind::PostProcess`] shader,
+/// change its parameters through [`PostEffect::passes`] or
+/// [`PostEffect::set_uniform`]
+///
+/// Cheap to clone, clones share their parameters
+#[derive(Clone)]
+pub struct PostEffect {
+    passes: Vec<Material>,
+}
+
+impl PostEffect {
+    /// Effect made of a single pass
+    ///
+    /// Panics unless it's a [`ShaderKind::PostProcess`] material
+    pub fn new(material: Material) -> Self {
+        Self::with_passes(vec![material])
+    }
+
+    /// Runs `passes` in order, each one sees the output of the one before as
+    /// `source` and what the effect started from as `original`
+    ///
+    /// Panics unless they're all [`ShaderKind::PostProcess`] materials
+    pub fn with_passes(passes: Vec<Material>) -> Self {
+        assert!(
+            passes
+                .iter()
+                .all(|pass| pass.shader().kind() == ShaderKind::PostProcess),
+            "post effects are made of post-process materials"
+        );
+        Self { passes }
+    }
+
+    pub fn passes(&self) -> &[Material] {
+        &self.passes
+    }
+
+    /// Writes the uniform at `index` of every pass
+    ///
+    /// The built-in effects keep their parameters as a `[f32; 4]` at index 0, in
+    /// the same order their constructor takes them, e.g. `[intensity, radius,
+    /// softness, 0.0]` for [`PostEffect::vignette`]
+    pub fn set_uniform<T: Pod>(&self, index: usize, value: &T) {
+        for pass in &self.passes {
+            pass.set_uniform(index, value);
+        }
+    }
+
+    /// Darkens the corners, `radius` is where it starts with 0 in the
+    /// center and 1 in the corners, and `softness` how far it fades in
+    pub fn vignette(
+        gfx: &Gfx,
+        intensity: f32,
+        radius: f32,
+        softness: f32,
+    ) -> Result<Self, RenderError> {
+        Self::built_in(gfx, &[VIGNETTE_SHADER], [intensity, radius, softness, 0.0])
+    }
+
+    /// Old monitor look, darkens every other `line_height` pixels by
+    /// `scanlines` and bulges the picture out by `curvature`
+    pub fn crt(
+        gfx: &Gfx,
+        scanlines: f32,
+        curvature: f32,
+        line_height: f32,
+    ) -> Result<Self, RenderError> {
+        Self::built_in(gfx, &[CRT_SHADER], [scanlines, curvature, line_height, 0.0])
+    }
+
+    /// Splits red and blue apart towards the edges by up to `amount` pixels
+    pub fn chromatic_aberration(gfx: &Gfx, amount: f32) -> Result<Self, RenderError> {
+        Self::built_in(gfx, &[CHROMATIC_ABERRATION_SHADER], [amount, 0.0, 0.0, 0.0])
+    }
+
+    /// Gaussian blur reaching `radius` pixels out
+    pub fn blur(gfx: &Gfx, radius: f32) -> Result<Self, RenderError> {
+        let horizontal = blur_shader([1.0, 0.0], 0);
+        let vertical = blur_shader([0.0, 1.0], 0);
+        Self::built_in(gfx, &[&horizontal, &vertical], [radius, 0.0, 0.0, 0.0])
+    }
+
+    /// Makes everything brighter than `threshold` glow, `radius` pixels out
+    pub fn bloom(
+        gfx: &Gfx,
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    ) -> Result<Self, RenderError> {
+        let horizontal = blur_shader([1.0, 0.0], 2);
+        let vertical = blur_shader([0.0, 1.0], 2);
+        Self::built_in(
+            gfx,
+            &[
+                BLOOM_THRESHOLD_SHADER,
+                &horizontal,
+                &vertical,
+                BLOOM_COMBINE_SHADER,
+            ],
+            [threshold, intensity, radius, 0.0],
+        )
+    }
+
+    /// Looks every color up in `lut` and mixes it in by `intensity`
+    ///
+    /// The lut has one square slice per shade of blue side by side, red going
+    /// right and green going down in each, edit a [`neutral_lut`] to make one
+    pub fn color_grade(gfx: &Gfx, lut: &Sprite, intensity: f32) -> Result<Self, RenderError> {
+        let (_, size) = lut.size();
+        let shader = Shader::new(
+            gfx,
+            ShaderKind::PostProcess,
+            COLOR_GRADE_SHADER,
+            &[ShaderBinding::Uniform(16), ShaderBinding::Texture],
+        )?;
+        let material = Material::new(gfx, &shader)?;
+        material.set_uniform(0, &[intensity, size as f32, 0.0, 0.0]);
+        // blends between neighbouring entries
+        material.set_texture(1, &lut.with_sampler(gfx, Sampler::linear()));
+        Ok(Self::new(material))
+    }
+
+    fn built_in(gfx: &Gfx, sources: &[&str], params: [f32; 4]) -> Result<Self, RenderError> {
+        let passes = sources
+            .iter()
+            .map(|source| {
+                let shader = Shader::new(gfx, ShaderKind::PostProcess, source, PARAMS)?;
+                Material::new(gfx, &shader)
+            })
+            .collect::<Result<_, RenderError>>()?;
+        let effect = Self { passes };
+        effect.set_uniform(0, &params);
+        Ok(effect)
+    }
+}
+
+impl fmt::Debug for PostEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PostEffect")
+            .field("passes", &self.passes)
+            .finish()
+    }
+}
+
+fn blur_shader([x, y]: [f32; 2], radius: usize) -> String {
+    format!(
+        "const DIRECTION = vec2<f32>({x:?}, {y:?});\nconst RADIUS: i32 = {radius};\n{BLUR_SHADER}"
+    )
+}
+
+/// Lookup table for [`PostEffect::color_grade`] that leaves colors as they are,
+/// `size` shades per channel
+pub fn neutral_lut(size: u32) -> image::RgbaImage {
+    let size = size.max(2);
+    let shade = |value: u32| (value * 255 / (size - 1)) as u8;
+    image::RgbaImage::from_fn(size * size, size, |x, y| {
+        image::Rgba([shade(x % size), shade(y), shade(x / size), 255])
+    })
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Zeroable, Pod)]
+struct PostGlobals {
+    rect: [f32; 4],
+    size: [f32; 2],
+    time: f32,
+    _padding: f32,
+}
+
+/// Textures the scene and effects ping-pong between, with a bind group for
+/// every texture as `source` with every texture as `original`
+struct Targets {
+    textures: [RenderTarget; 3],
+    bind_groups: Vec<wgpu::BindGroup>,
+}
+
+/// Runs the [`PostEffect`]s of every viewport over the drawn scene
+pub(crate) struct PostProcessor {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    globals: wgpu::Buffer,
+    targets: Option<Targets>,
+}
+
+impl PostProcessor {
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let texture = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post-process bind group layout"),
+            entries: &[
+                texture(0),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture(3),
+            ],
+        });
+
+        let sampler = Sampler::linear().create(device, Some("Post-process sampler"));
+        let globals = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post-process globals"),
+            size: std::mem::size_of::<PostGlobals>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            layout,
+            sampler,
+            globals,
+            targets: None,
+        }
+    }
+
+    /// Pipeline for a post-process shader, `source` without the prelude
+    pub(crate) fn create_pipeline(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        label: &str,
+        source: &str,
+        material_layout: Option<&wgpu::BindGroupLayout>,
+    ) -> Result<wgpu::RenderPipeline, RenderError> {
+        let mut layouts = vec![&self.layout];
+        layouts.extend(material_layout);
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(format!("{POST_PRELUDE}\n{source}").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post-process pipeline layout"),
+            bind_group_layouts: &layouts,
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::all(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        match smol::block_on(device.pop_error_scope()) {
+            Some(error) => Err(RenderError::Shader(error.to_string())),
+            None => Ok(pipeline),
+        }
+    }
+
+    /// Where the scene gets drawn before the effects run over it,
+    /// remade whenever `size` changes
+    pub(crate) fn scene(
+        &mut self,
+        device: &wgpu::Device,
+        texture_layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> RenderTarget {
+        if self
+            .targets
+            .as_ref()
+            .is_none_or(|targets| targets.textures[0].size() != size)
+        {
+            let textures = [0, 1, 2].map(|_| {
+                RenderTarget::create(
+                    device,
+                    texture_layout,
+                    format,
+                    size,
+                    Sampler::nearest(),
+                    Some("Post-process target"),
+                )
+            });
+            let views = textures.each_ref().map(RenderTarget::view);
+            let mut bind_groups = Vec::new();
+            for source in &views {
+                for original in &views {
+                    bind_groups.push(device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("Post-process bind group"),
+                        layout: &self.layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: wgpu::BindingResource::TextureView(source),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::Sampler(&self.sampler),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: self.globals.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 3,
+                                resource: wgpu::BindingResource::TextureView(original),
+                            },
+                        ],
+                    }));
+                }
+            }
+            self.targets = Some(Targets {
+                textures,
+                bind_groups,
+            });
+        }
+
+        self.targets.as_ref().unwrap().textures[0].clone()
+    }
+
+    /// Runs the effects of every viewport over what [`PostProcessor::scene`] got,
+    /// only inside the viewport, and hands back the texture that ends up with the result
+    pub(crate) fn run(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        viewports: &[Viewport],
+        time: f32,
+    ) -> RenderTarget {
+        let targets = self.targets.as_ref().expect("scene is drawn first");
+        let (width, height) = targets.textures[0].size();
+        let texture_size = Vec2::new(width as f32, height as f32);
+
+        // every texture starts out with the scene so whatever is outside
+        // the viewports carries over no matter which one ends up the result
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Post-process encoder"),
+        });
+        for texture in &targets.textures[1..] {
+            encoder.copy_texture_to_texture(
+                targets.textures[0].texture().as_image_copy(),
+                texture.texture().as_image_copy(),
+                targets.textures[0].texture().size(),
+            );
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let mut current = 0;
+        for viewport in viewports
+            .iter()
+            .filter(|viewport| !viewport.post.is_empty())
+        {
+            let Some(scissor) = viewport.visible_rect(texture_size) else {
+                continue;
+            };
+            let rect = Rect::new(scissor.min / texture_size, scissor.max / texture_size);
+            let globals = PostGlobals {
+                rect: [rect.min.x, rect.min.y, rect.max.x, rect.max.y],
+                size: [width as f32, height as f32],
+                time,
+                _padding: 0.0,
+            };
+            // each viewport goes in its own submission since they share the globals
+            queue.write_buffer(&self.globals, 0, bytemuck::bytes_of(&globals));
+
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Post-process encoder"),
+            });
+            for effect in &viewport.post {
+                let original = current;
+                let others = [(original + 1) % 3, (original + 2) % 3];
+                let mut source = original;
+                for (i, pass) in effect.passes.iter().enumerate() {
+                    let destination = others[i % 2];
+                    let view = targets.textures[destination].view();
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("Post-process pass"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: wgpu::StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                    render_pass.set_scissor_rect(
+                        scissor.min.x as u32,
+                        scissor.min.y as u32,
+                        scissor.width() as u32,
+                        scissor.height() as u32,
+                    );
+                    render_pass.set_pipeline(&pass.shader().pipeline());
+                    render_pass.set_bind_group(0, &targets.bind_groups[source * 3 + original], &[]);
+                    if let Some(bind_group) = pass.bind_group() {
+                        render_pass.set_bind_group(1, &*bind_group, &[]);
+                    }
+                    render_pass.draw(0..3, 0..1);
+                    source = destination;
+                }
+                current = source;
+            }
+
+            // bring the other textures up to date inside the viewport too
+            let origin = wgpu::Origin3d {
+                x: scissor.min.x as u32,
+                y: scissor.min.y as u32,
+                z: 0,
+            };
+            let extent = wgpu::Extent3d {
+                width: scissor.width() as u32,
+                height: scissor.height() as u32,
+                depth_or_array_layers: 1,
+            };
+            for other in [(current + 1) % 3, (current + 2) % 3] {
+                encoder.copy_texture_to_texture(
+                    wgpu::ImageCopyTexture {
+                        origin,
+                        ..targets.textures[current].texture().as_image_copy()
+                    },
+                    wgpu::ImageCopyTexture {
+                        origin,
+                        ..targets.textures[other].texture().as_image_copy()
+                    },
+                    extent,
+                );
+            }
+            queue.submit(std::iter::once(encoder.finish()));
+        }
+
+        targets.textures[current].clone()
+    }
+}
@@ -6,13 +6,33 @@ use wgpu::VertexAttribute;
 #[derive(Clone, Copy, Debug, Zeroable, Pod)]
 pub struct Color {
     pub r: f32,
-    pub b: f32,
     pub g: f32,
+    pub b: f32,
+    pub a: f32,
 }
 
 impl Color {
-    pub fn new(r: f32, g: f32, b: f32) -> Self {
-        Self { r, g, b }
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0);
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0);
+    pub const TRANSPARENT: Self = Self::rgba(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
+        Self::rgba(r, g, b, 1.0)
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl From<Color> for wgpu::Color {
+    fn from(value: Color) -> Self {
+        Self {
+            r: value.r as f64,
+            g: value.g as f64,
+            b: value.b as f64,
+            a: value.a as f64,
+        }
     }
 }
 
@@ -41,7 +61,7 @@ impl From<(Vec3, Color)> for VertexColored {
 impl Vertex for VertexColored {
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         const ATTRIBUTES: &[VertexAttribute] =
-            &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3];
+            &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
 
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<VertexColored>() as wgpu::BufferAddress,
@@ -55,13 +75,20 @@ impl Vertex for VertexColored {
 pub struct VertexTextured {
     pub position: Vec3,
     pub tex_coords: Vec2,
+    /// Multiplied with the texture color
+    pub color: Color,
 }
 
 impl VertexTextured {
-    pub fn new(mut pos: Vec3, tex_coords: Vec2) -> Self {
+    pub fn new(pos: Vec3, tex_coords: Vec2) -> Self {
+        Self::tinted(pos, tex_coords, Color::WHITE)
+    }
+
+    pub fn tinted(pos: Vec3, tex_coords: Vec2, color: Color) -> Self {
         Self {
             position: pos,
             tex_coords,
+            color,
         }
     }
 }
@@ -75,7 +102,7 @@ impl From<(Vec3, Vec2)> for VertexTextured {
 impl Vertex for VertexTextured {
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         const ATTRIBUTES: &[VertexAttribute] =
-            &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2];
+            &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x4];
 
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<VertexTextured>() as wgpu::BufferAddress,
@@ -197,6 +224,10 @@ impl Quad<VertexTextured> {
     }
 }
 
+impl<V: Vertex> Quad<V> {
+    pub const INDICES: &'static IndexBuffer<6> = &[0, 1, 2, 2, 1, 3];
+}
+
 impl<V: Vertex> Primitive<4, 6> for Quad<V> {
     type T = V;
     fn vertices(&self) -> VertexBuffer<4, Self::T> {
@@ -204,6 +235,6 @@ impl<V: Vertex> Primitive<4, 6> for Quad<V> {
     }
 
     fn indices(&self) -> Option<&'static IndexBuffer<6>> {
-        Some(&[0, 1, 2, 2, 1, 3])
+        Some(Self::INDICES)
     }
 }
@@ -0,0 +1,78 @@
+use crate::RenderError;
+
+/// Little endian reads out of binary asset files
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    /// Wraps the message when the data runs out, so errors name the format being read
+    error: fn(String) -> RenderError,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], error: fn(String) -> RenderError) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            error,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    /// Everything that hasn't been read yet
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.offset.min(self.bytes.len())..]
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], RenderError> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or_else(|| (self.error)("unexpected end of file".into()))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    pub(crate) fn skip(&mut self, len: usize) -> Result<(), RenderError> {
+        self.take(len).map(|_| ())
+    }
+
+    /// Reader over the next `len` bytes, this one continues after them
+    pub(crate) fn sub(&mut self, len: usize) -> Result<Reader<'a>, RenderError> {
+        Ok(Reader::new(self.take(len)?, self.error))
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], RenderError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, RenderError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, RenderError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    pub(crate) fn i16(&mut self) -> Result<i16, RenderError> {
+        self.array().map(i16::from_le_bytes)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, RenderError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub(crate) fn i32(&mut self) -> Result<i32, RenderError> {
+        self.array().map(i32::from_le_bytes)
+    }
+
+    /// Length prefixed utf-8, the way Aseprite stores strings
+    pub(crate) fn string(&mut self) -> Result<String, RenderError> {
+        let len = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+}
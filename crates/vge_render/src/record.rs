@@ -0,0 +1,140 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::{Gfx, RenderError, pool::WorkerPool};
+
+/// Image and every file it goes in, repeated frames are encoded once and copied
+type Write = (image::RgbaImage, Vec<PathBuf>);
+
+/// Most threads encoding PNGs at the same time
+const MAX_WRITERS: usize = 4;
+
+/// Frames waiting to be encoded before capturing blocks, keeps memory
+/// from growing when frames come in faster than they can be written
+const QUEUE_SIZE: usize = 8;
+
+/// Saves frames as numbered PNGs, `frame_000000.png` onwards, for
+/// putting together a video afterwards
+///
+/// Encoding happens on a few threads of its own, rendering only waits for
+/// them once they fall behind by more than a handful of frames
+pub struct FrameRecorder {
+    dir: PathBuf,
+    fps: u32,
+    started: Option<Instant>,
+    frames: u64,
+    writers: WorkerPool<Write>,
+    /// First file that couldn't be written, reported on the next capture
+    error: Arc<Mutex<Option<RenderError>>>,
+}
+
+impl FrameRecorder {
+    /// Records into `dir` at `fps` frames per second, the directory gets created if needed
+    pub fn new(dir: impl Into<PathBuf>, fps: u32) -> Result<Self, RenderError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        let error = Arc::new(Mutex::new(None));
+        let writers = {
+            let error = error.clone();
+            WorkerPool::new(
+                "vge frame recorder",
+                MAX_WRITERS,
+                Some(QUEUE_SIZE),
+                move |(image, paths): Write| {
+                    if let Err(write_error) = save(&image, &paths) {
+                        error.lock().unwrap().get_or_insert(write_error);
+                    }
+                },
+            )?
+        };
+
+        Ok(Self {
+            dir,
+            fps: fps.max(1),
+            started: None,
+            frames: 0,
+            writers,
+            error,
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    /// Frames saved or waiting to be saved so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Call after every render, keeps the video in step with real time by
+    /// skipping frames when rendering faster than `fps` and repeating the
+    /// last one when slower
+    pub fn capture(&mut self, gfx: &Gfx) -> Result<(), RenderError> {
+        let now = Instant::now();
+        let started = *self.started.get_or_insert(now);
+        // the first frame goes out right away
+        let due = ((now - started).as_secs_f64() * self.fps as f64) as u64 + 1;
+        if due <= self.frames {
+            return self.check();
+        }
+
+        let image = gfx.screenshot()?;
+        self.write(image, due - self.frames)
+    }
+
+    /// Saves exactly one frame for the last render, for games stepped by a fixed
+    /// `1 / fps` seconds per frame regardless of how long rendering takes
+    pub fn record_frame(&mut self, gfx: &Gfx) -> Result<(), RenderError> {
+        let image = gfx.screenshot()?;
+        self.write(image, 1)
+    }
+
+    /// Waits for every frame to be written
+    pub fn finish(mut self) -> Result<(), RenderError> {
+        self.writers.join();
+        self.check()
+    }
+
+    fn write(&mut self, image: image::RgbaImage, count: u64) -> Result<(), RenderError> {
+        let paths = (self.frames..self.frames + count)
+            .map(|frame| self.dir.join(format!("frame_{frame:06}.png")))
+            .collect();
+        // the threads only stop once the recorder is gone
+        let _ = self.writers.send((image, paths));
+        self.frames += count;
+        self.check()
+    }
+
+    fn check(&self) -> Result<(), RenderError> {
+        match self.error.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for FrameRecorder {
+    fn drop(&mut self) {
+        self.writers.join();
+    }
+}
+
+fn save(image: &image::RgbaImage, paths: &[PathBuf]) -> Result<(), RenderError> {
+    let Some((first, rest)) = paths.split_first() else {
+        return Ok(());
+    };
+    image.save(first)?;
+    for path in rest {
+        std::fs::copy(first, path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+/// How texels are blended when a texture is drawn bigger or smaller than it is
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// Blocky, what pixel art wants
+    #[default]
+    Nearest,
+    /// Smooth
+    Linear,
+}
+
+impl From<Filter> for wgpu::FilterMode {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::Nearest => wgpu::FilterMode::Nearest,
+            Filter::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+/// What's sampled outside of the texture, when uvs go past 0 or 1
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Wrap {
+    /// Stretches the edge texels
+    #[default]
+    Clamp,
+    /// Tiles the texture
+    Repeat,
+    /// Tiles the texture, flipping every other copy
+    Mirror,
+}
+
+impl From<Wrap> for wgpu::AddressMode {
+    fn from(wrap: Wrap) -> Self {
+        match wrap {
+            Wrap::Clamp => wgpu::AddressMode::ClampToEdge,
+            Wrap::Repeat => wgpu::AddressMode::Repeat,
+            Wrap::Mirror => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// How a texture gets sampled when drawn, set per texture
+///
+/// The default is smooth when zoomed in and blocky when zoomed out,
+/// clamped at the edges
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sampler {
+    /// Used when the texture is drawn bigger than it is
+    pub mag_filter: Filter,
+    /// Used when the texture is drawn smaller than it is
+    pub min_filter: Filter,
+    /// Used between mip levels
+    pub mipmap_filter: Filter,
+    pub wrap_u: Wrap,
+    pub wrap_v: Wrap,
+    /// Up to 16 samples for textures seen at an angle, only takes
+    /// effect when every filter is [`Filter::Linear`]
+    pub anisotropy: u16,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Self {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Nearest,
+            mipmap_filter: Filter::Nearest,
+            wrap_u: Wrap::Clamp,
+            wrap_v: Wrap::Clamp,
+            anisotropy: 1,
+        }
+    }
+}
+
+impl Sampler {
+    /// Blocky at every size, for pixel art
+    pub fn nearest() -> Self {
+        Self::default().filter(Filter::Nearest)
+    }
+
+    /// Smooth at every size, trilinear with mipmaps
+    pub fn linear() -> Self {
+        Self::default().filter(Filter::Linear)
+    }
+
+    /// Sets every filter at once
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.mag_filter = filter;
+        self.min_filter = filter;
+        self.mipmap_filter = filter;
+        self
+    }
+
+    /// Sets the wrapping in both directions
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap_u = wrap;
+        self.wrap_v = wrap;
+        self
+    }
+
+    pub fn anisotropy(mut self, anisotropy: u16) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    pub(crate) fn create(&self, device: &wgpu::Device, label: Option<&str>) -> wgpu::Sampler {
+        // wgpu rejects anisotropic filtering unless everything is linear
+        let linear = [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .iter()
+            .all(|filter| *filter == Filter::Linear);
+        let anisotropy_clamp = if linear {
+            self.anisotropy.clamp(1, 16)
+        } else {
+            1
+        };
+
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label,
+            address_mode_u: self.wrap_u.into(),
+            address_mode_v: self.wrap_v.into(),
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: self.mag_filter.into(),
+            min_filter: self.min_filter.into(),
+            mipmap_filter: self.mipmap_filter.into(),
+            anisotropy_clamp,
+            ..Default::default()
+        })
+    }
+}
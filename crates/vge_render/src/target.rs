@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use crate::{
+    Frame, Gfx,
+    camera::{Camera2D, Viewport},
+    mesh::{Sprite, TexturedQuad},
+    sampler::Sampler,
+};
+
+/// Texture that can be drawn into like the window and then drawn as a
+/// sprite, for minimaps, reflections or anything drawn once and reused
+///
+/// Keeps what was drawn into it until it's drawn into again with a
+/// [`Frame::clear_color`], starts out transparent. It can't be drawn
+/// as a sprite in the frame that draws into it
+///
+/// Cheap to clone, clones share the same texture
+#[derive(Clone)]
+pub struct RenderTarget {
+    sprite: Sprite,
+}
+
+impl RenderTarget {
+    /// Sampled with [`Gfx::default_sampler`]
+    pub fn new(gfx: &Gfx, width: u32, height: u32) -> Self {
+        Self::with_sampler(gfx, width, height, gfx.default_sampler())
+    }
+
+    pub fn with_sampler(gfx: &Gfx, width: u32, height: u32, sampler: Sampler) -> Self {
+        Self::create(
+            &gfx.device,
+            &gfx.texture_bind_group_layout,
+            gfx.format(),
+            (width, height),
+            sampler,
+            Some("Render target"),
+        )
+    }
+
+    pub(crate) fn create(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        (width, height): (u32, u32),
+        sampler: Sampler,
+        label: Option<&str>,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let texture = TexturedQuad::from_texture_sampled(device, layout, texture, sampler, label);
+
+        Self {
+            sprite: Sprite::from_texture(Arc::new(texture)),
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.sprite.size()
+    }
+
+    /// What was drawn so far, draws of the sprite in the same frame as drawing
+    /// into the target see the new contents
+    pub fn sprite(&self) -> &Sprite {
+        &self.sprite
+    }
+
+    pub(crate) fn texture(&self) -> &wgpu::Texture {
+        &self.sprite.texture.texture
+    }
+
+    pub(crate) fn view(&self) -> wgpu::TextureView {
+        self.texture()
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.sprite.texture.bind_group
+    }
+}
+
+/// Everything drawn into a [`RenderTarget`] in one go, part of a [`Frame`]
+/// so it's drawn before anything in the frame
+#[derive(Clone)]
+pub struct TargetDraw {
+    pub target: RenderTarget,
+    /// Without a [`Frame::clear_color`] this draws over what the target already had
+    pub frame: Frame,
+    /// Cameras to draw through, relative to the target instead of the window
+    pub viewports: Vec<Viewport>,
+}
+
+impl TargetDraw {
+    /// Through a camera centered on the origin, like the window's default
+    pub fn new(target: RenderTarget, frame: Frame) -> Self {
+        Self {
+            target,
+            frame,
+            viewports: vec![Viewport::default()],
+        }
+    }
+
+    pub fn camera(mut self, camera: Camera2D) -> Self {
+        self.viewports = vec![Viewport::new(camera)];
+        self
+    }
+
+    pub fn viewports(mut self, viewports: Vec<Viewport>) -> Self {
+        self.viewports = viewports;
+        self
+    }
+}
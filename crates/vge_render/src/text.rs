@@ -0,0 +1,828 @@
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use vge_math::{Rect, Vec2};
+
+use crate::{
+    Gfx, RenderError,
+    atlas::ShelfPacker,
+    bitmap_font::{BitmapFont, BitmapGlyph, BmFontDesc},
+    mesh::{DrawSprite, Sprite, Text, TexturedQuad},
+    sampler::Sampler,
+};
+
+static NEXT_FONT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Size the glyph atlas starts out at, it doubles whenever a frame needs more room
+const INITIAL_ATLAS_SIZE: u32 = 512;
+
+/// Empty texels around every glyph in the atlas so filtering doesn't pick up the neighbours
+const GLYPH_PADDING: u32 = 1;
+
+/// Size [`Text`] defaults to for TrueType and OpenType fonts
+const DEFAULT_TEXT_SIZE: f32 = 16.0;
+
+/// Glyph id bitmap fonts use for characters they don't have, -1 in BMFont files
+const MISSING_GLYPH: u32 = u32::MAX;
+
+enum FontData {
+    Vector(fontdue::Font),
+    Bitmap(BitmapFont),
+}
+
+/// Vertical metrics of a font at some size, y pointing up from the baseline
+struct LineMetrics {
+    ascent: f32,
+    descent: f32,
+    line_spacing: f32,
+}
+
+/// Placement of a single glyph relative to the pen on the baseline
+#[derive(Default)]
+struct GlyphMetrics {
+    min: Vec2,
+    size: Vec2,
+    advance: f32,
+}
+
+/// Font to draw [`Text`] with
+///
+/// TrueType and OpenType fonts get rasterized on demand at whatever size text is drawn at,
+/// bitmap fonts draw their pre-rendered glyphs without filtering and only scale by whole numbers.
+/// Cheap to clone, clones share the same font data
+#[derive(Clone)]
+pub struct Font {
+    /// Identifies the font in the glyph atlas
+    pub(crate) id: u64,
+    data: Arc<FontData>,
+}
+
+impl Font {
+    fn new(data: FontData) -> Self {
+        Self {
+            id: NEXT_FONT_ID.fetch_add(1, Ordering::Relaxed),
+            data: Arc::new(data),
+        }
+    }
+
+    /// TrueType or OpenType font
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RenderError> {
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())
+            .map_err(RenderError::Font)?;
+        Ok(Self::new(FontData::Vector(font)))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RenderError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// AngelCode BMFont in either the text or the binary format,
+    /// the pages are loaded from next to the `.fnt` file
+    pub fn load_bmfont(gfx: &Gfx, path: impl AsRef<Path>) -> Result<Self, RenderError> {
+        let path = path.as_ref();
+        Self::bmfont(
+            &gfx.device,
+            &gfx.queue,
+            &gfx.texture_bind_group_layout,
+            &std::fs::read(path)?,
+            path.parent().unwrap_or(Path::new("")),
+        )
+    }
+
+    /// BMFont with its pages loaded from `dir`
+    pub(crate) fn bmfont(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        fnt: &[u8],
+        dir: &Path,
+    ) -> Result<Self, RenderError> {
+        let desc = BmFontDesc::parse(fnt)?;
+        let pages = desc
+            .pages
+            .iter()
+            .map(|file| image::open(dir.join(file)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::bitmap(device, queue, layout, desc, &pages)
+    }
+
+    /// BMFont with its pages already loaded, in the order the `.fnt` file lists them
+    pub fn bmfont_from_bytes(
+        gfx: &Gfx,
+        fnt: &[u8],
+        pages: &[image::DynamicImage],
+    ) -> Result<Self, RenderError> {
+        let desc = BmFontDesc::parse(fnt)?;
+        Self::bitmap(
+            &gfx.device,
+            &gfx.queue,
+            &gfx.texture_bind_group_layout,
+            desc,
+            pages,
+        )
+    }
+
+    /// Monospace font sheet cut up into `cell_size` cells, `chars` lists the character
+    /// in each cell going left to right and then top to bottom
+    pub fn grid(
+        gfx: &Gfx,
+        image: &image::DynamicImage,
+        cell_size: (u32, u32),
+        chars: &str,
+    ) -> Result<Self, RenderError> {
+        let (width, height) = cell_size;
+        let columns = image.width().checked_div(width).unwrap_or(0);
+        let rows = image.height().checked_div(height).unwrap_or(0);
+        let count = chars.chars().count();
+        if count as u32 > columns * rows {
+            return Err(RenderError::BitmapFont(format!(
+                "{count} characters but only room for {} cells",
+                columns * rows
+            )));
+        }
+
+        let glyphs = chars
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| {
+                let glyph = BitmapGlyph {
+                    page: 0,
+                    x: i as u32 % columns * width,
+                    y: i as u32 / columns * height,
+                    width,
+                    height,
+                    offset: Vec2::ZERO,
+                    advance: width as f32,
+                };
+                (ch as u32, glyph)
+            })
+            .collect();
+
+        let desc = BmFontDesc {
+            line_height: height as f32,
+            base: height as f32,
+            pages: vec![String::new()],
+            glyphs,
+            kerning: HashMap::new(),
+        };
+        Self::bitmap(
+            &gfx.device,
+            &gfx.queue,
+            &gfx.texture_bind_group_layout,
+            desc,
+            std::slice::from_ref(image),
+        )
+    }
+
+    /// Shorthand for loading the image of a [`Font::grid`]
+    pub fn load_grid(
+        gfx: &Gfx,
+        path: impl AsRef<Path>,
+        cell_size: (u32, u32),
+        chars: &str,
+    ) -> Result<Self, RenderError> {
+        let image = image::open(path)?;
+        Self::grid(gfx, &image, cell_size, chars)
+    }
+
+    fn bitmap(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        desc: BmFontDesc,
+        pages: &[image::DynamicImage],
+    ) -> Result<Self, RenderError> {
+        if pages.len() < desc.pages.len() {
+            return Err(RenderError::BitmapFont(format!(
+                "expected {} pages, got {}",
+                desc.pages.len(),
+                pages.len()
+            )));
+        }
+
+        let pages = pages
+            .iter()
+            .map(|page| {
+                TexturedQuad::from_image_sampled(
+                    device,
+                    queue,
+                    layout,
+                    page,
+                    Sampler::nearest(),
+                    Some("Font page"),
+                )
+                .map(Arc::new)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(FontData::Bitmap(BitmapFont {
+            pages,
+            line_height: desc.line_height,
+            base: desc.base,
+            glyphs: desc.glyphs,
+            kerning: desc.kerning,
+        })))
+    }
+
+    /// Shorthand for [`Text::new`]
+    pub fn text(&self, text: impl Into<String>, position: Vec2) -> Text {
+        Text::new(self.clone(), text, position)
+    }
+
+    /// Size text gets drawn at unless told otherwise,
+    /// the line height of bitmap fonts so they come out texel for texel
+    pub fn default_size(&self) -> f32 {
+        match &*self.data {
+            FontData::Vector(_) => DEFAULT_TEXT_SIZE,
+            FontData::Bitmap(bitmap) => bitmap.line_height,
+        }
+    }
+
+    /// Distance from the baseline to the top of the tallest glyphs at `size` pixels
+    pub fn ascent(&self, size: f32) -> f32 {
+        self.line_metrics(size).ascent
+    }
+
+    /// Distance from one baseline to the next at `size` pixels
+    pub fn line_spacing(&self, size: f32) -> f32 {
+        self.line_metrics(size).line_spacing
+    }
+
+    /// Bitmap fonts only scale by whole numbers, `size` is rounded to the closest multiple
+    /// of their line height
+    fn bitmap_scale(bitmap: &BitmapFont, size: f32) -> f32 {
+        (size / bitmap.line_height).round().max(1.0)
+    }
+
+    fn line_metrics(&self, size: f32) -> LineMetrics {
+        match &*self.data {
+            // fonts without horizontal metrics still need somewhere to put the baseline
+            FontData::Vector(font) => font.horizontal_line_metrics(size).map_or(
+                LineMetrics {
+                    ascent: size,
+                    descent: 0.0,
+                    line_spacing: size,
+                },
+                |metrics| LineMetrics {
+                    ascent: metrics.ascent,
+                    descent: metrics.descent,
+                    line_spacing: metrics.new_line_size,
+                },
+            ),
+            FontData::Bitmap(bitmap) => {
+                let scale = Self::bitmap_scale(bitmap, size);
+                LineMetrics {
+                    ascent: bitmap.base * scale,
+                    descent: (bitmap.base - bitmap.line_height) * scale,
+                    line_spacing: bitmap.line_height * scale,
+                }
+            }
+        }
+    }
+
+    fn glyph_index(&self, ch: char) -> u32 {
+        match &*self.data {
+            FontData::Vector(font) => font.lookup_glyph_index(ch) as u32,
+            FontData::Bitmap(bitmap) if bitmap.glyphs.contains_key(&(ch as u32)) => ch as u32,
+            FontData::Bitmap(_) => MISSING_GLYPH,
+        }
+    }
+
+    fn kern(&self, previous: u32, glyph: u32, size: f32) -> f32 {
+        match &*self.data {
+            FontData::Vector(font) => font
+                .horizontal_kern_indexed(previous as u16, glyph as u16, size)
+                .unwrap_or(0.0),
+            FontData::Bitmap(bitmap) => bitmap
+                .kerning
+                .get(&(previous, glyph))
+                .map_or(0.0, |amount| amount * Self::bitmap_scale(bitmap, size)),
+        }
+    }
+
+    fn glyph_metrics(&self, glyph: u32, size: f32) -> GlyphMetrics {
+        match &*self.data {
+            FontData::Vector(font) => {
+                let metrics = font.metrics_indexed(glyph as u16, size);
+                GlyphMetrics {
+                    min: Vec2::new(metrics.xmin as f32, metrics.ymin as f32),
+                    size: Vec2::new(metrics.width as f32, metrics.height as f32),
+                    advance: metrics.advance_width,
+                }
+            }
+            FontData::Bitmap(bitmap) => {
+                let Some(glyph) = bitmap.glyphs.get(&glyph) else {
+                    return GlyphMetrics::default();
+                };
+
+                let scale = Self::bitmap_scale(bitmap, size);
+                let height = glyph.height as f32;
+                GlyphMetrics {
+                    min: Vec2::new(glyph.offset.x, bitmap.base - glyph.offset.y - height) * scale,
+                    size: Vec2::new(glyph.width as f32, height) * scale,
+                    advance: glyph.advance * scale,
+                }
+            }
+        }
+    }
+
+    /// Horizontal distance from `glyph` to the next one, kerned against the glyph before it
+    fn advance(&self, previous: Option<u32>, glyph: u32, size: f32) -> f32 {
+        let kern = previous.map_or(0.0, |previous| self.kern(previous, glyph, size));
+        kern + self.glyph_metrics(glyph, size).advance
+    }
+
+    /// Width of a line, trailing whitespace included
+    fn measure(&self, chars: &[char], size: f32) -> f32 {
+        let mut previous = None;
+        chars
+            .iter()
+            .map(|ch| {
+                let glyph = self.glyph_index(*ch);
+                let advance = self.advance(previous, glyph, size);
+                previous = Some(glyph);
+                advance
+            })
+            .sum()
+    }
+}
+
+/// Horizontal alignment of the lines within a [`Text`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Single glyph placed by [`Text::layout`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayoutGlyph {
+    pub ch: char,
+    pub(crate) index: u32,
+    /// Where the glyph bitmap goes, relative to the position of the text
+    pub rect: Rect,
+}
+
+/// Glyphs of a [`Text`] after wrapping and alignment
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<LayoutGlyph>,
+    /// Bounds of the text relative to its position, `origin` already applied
+    pub bounds: Rect,
+    /// Number of lines after wrapping
+    pub lines: usize,
+}
+
+/// Breaks `text` into lines, at newlines and wherever a word would go past `max_width`
+fn wrap(font: &Font, chars: &[char], size: f32, max_width: Option<f32>) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+
+    for end in chars
+        .iter()
+        .enumerate()
+        .filter(|(_, ch)| **ch == '\n')
+        .map(|(i, _)| i)
+        .chain([chars.len()])
+    {
+        let Some(max_width) = max_width else {
+            lines.push(start..end);
+            start = end + 1;
+            continue;
+        };
+
+        let mut line_start = start;
+        let mut word_start = start;
+        let mut width = 0.0;
+        let mut previous = None;
+
+        for i in start..end {
+            let ch = chars[i];
+            let glyph = font.glyph_index(ch);
+            let advance = font.advance(previous, glyph, size);
+            previous = Some(glyph);
+
+            if ch.is_whitespace() {
+                width += advance;
+                word_start = i + 1;
+                continue;
+            }
+
+            if width + advance > max_width && i > line_start {
+                // move the current word down, or break it up if it's longer than a whole line
+                let wrap_at = if word_start > line_start {
+                    word_start
+                } else {
+                    i
+                };
+                lines.push(line_start..wrap_at);
+                line_start = wrap_at;
+                word_start = wrap_at;
+                width = font.measure(&chars[wrap_at..i], size);
+                previous = (wrap_at < i).then(|| font.glyph_index(chars[i - 1]));
+                width += font.advance(previous, glyph, size);
+                previous = Some(glyph);
+                continue;
+            }
+
+            width += advance;
+        }
+
+        lines.push(line_start..end);
+        start = end + 1;
+    }
+
+    lines
+}
+
+pub(crate) fn layout(text: &Text) -> TextLayout {
+    let font = &text.font;
+    let size = text.size;
+    let chars: Vec<char> = text.text.chars().collect();
+    let metrics = font.line_metrics(size);
+    let line_advance = metrics.line_spacing * text.line_height;
+
+    let lines = wrap(font, &chars, size, text.max_width);
+    let widths: Vec<f32> = lines
+        .iter()
+        .map(|line| {
+            let trimmed = chars[line.clone()]
+                .iter()
+                .rposition(|ch| !ch.is_whitespace())
+                .map_or(0, |last| last + 1);
+            font.measure(&chars[line.start..line.start + trimmed], size)
+        })
+        .collect();
+
+    let width = text
+        .max_width
+        .unwrap_or_else(|| widths.iter().copied().fold(0.0, f32::max));
+    let height = metrics.ascent - metrics.descent + line_advance * (lines.len() - 1) as f32;
+    let size_box = Vec2::new(width, height);
+
+    // laid out from the top left corner, then shifted so `origin` ends up at the position
+    let anchor = (Vec2::new(0.0, -height) + size_box * text.origin).round();
+
+    let mut glyphs = Vec::new();
+    for (i, (line, line_width)) in lines.iter().zip(&widths).enumerate() {
+        let offset = match text.align {
+            Align::Left => 0.0,
+            Align::Center => (width - line_width) * 0.5,
+            Align::Right => width - line_width,
+        };
+        let baseline = -(metrics.ascent + line_advance * i as f32);
+
+        let mut x = offset;
+        let mut previous = None;
+        for ch in &chars[line.clone()] {
+            let index = font.glyph_index(*ch);
+            if let Some(previous) = previous {
+                x += font.kern(previous, index, size);
+            }
+
+            let glyph = font.glyph_metrics(index, size);
+            if glyph.size.x > 0.0 && glyph.size.y > 0.0 {
+                // whole texels keep the glyphs from getting blurry
+                let min = (Vec2::new(x, baseline) + glyph.min).round() - anchor;
+                glyphs.push(LayoutGlyph {
+                    ch: *ch,
+                    index,
+                    rect: Rect::new(min, min + glyph.size),
+                });
+            }
+
+            x += glyph.advance;
+            previous = Some(index);
+        }
+    }
+
+    TextLayout {
+        glyphs,
+        bounds: Rect::new(Vec2::new(0.0, -height), Vec2::new(width, 0.0)).translate(-anchor),
+        lines: lines.len(),
+    }
+}
+
+/// Where a rasterized glyph sits in the atlas, in texels
+#[derive(Clone, Copy)]
+struct AtlasGlyph {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Glyphs did not fit into the atlas
+struct AtlasFull;
+
+/// Texture every glyph gets rasterized into the first time it's drawn,
+/// coverage goes in the alpha channel so text can be tinted like any sprite
+pub(crate) struct GlyphAtlas {
+    texture: Arc<TexturedQuad>,
+    size: u32,
+    packer: ShelfPacker,
+    glyphs: HashMap<(u64, u16, u32), AtlasGlyph>,
+}
+
+impl GlyphAtlas {
+    pub(crate) fn new(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> Self {
+        Self {
+            texture: Arc::new(Self::create_texture(device, layout, INITIAL_ATLAS_SIZE)),
+            size: INITIAL_ATLAS_SIZE,
+            packer: ShelfPacker::new((INITIAL_ATLAS_SIZE, INITIAL_ATLAS_SIZE), GLYPH_PADDING),
+            glyphs: HashMap::new(),
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        size: u32,
+    ) -> TexturedQuad {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph atlas"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        TexturedQuad::from_texture(device, layout, texture, Some("Glyph atlas"))
+    }
+
+    /// Rasterizes any glyphs of `texts` that aren't in the atlas yet
+    /// and returns a sprite for every glyph
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        texts: &[Text],
+    ) -> Vec<DrawSprite> {
+        if texts.is_empty() {
+            return Vec::new();
+        }
+
+        let layouts: Vec<TextLayout> = texts.iter().map(Text::layout).collect();
+        loop {
+            match self.try_prepare(queue, texts, &layouts, false) {
+                Ok(sprites) => return sprites,
+                Err(AtlasFull) if self.size < device.limits().max_texture_dimension_2d => {
+                    self.size = (self.size * 2).min(device.limits().max_texture_dimension_2d);
+                    self.texture = Arc::new(Self::create_texture(device, layout, self.size));
+                    self.packer = ShelfPacker::new((self.size, self.size), GLYPH_PADDING);
+                    self.glyphs.clear();
+                }
+                Err(AtlasFull) => {
+                    // already as big as it gets, start over and drop whatever doesn't fit
+                    self.clear();
+                    return self
+                        .try_prepare(queue, texts, &layouts, true)
+                        .unwrap_or_default();
+                }
+            }
+        }
+    }
+
+    fn try_prepare(
+        &mut self,
+        queue: &wgpu::Queue,
+        texts: &[Text],
+        layouts: &[TextLayout],
+        skip_overflow: bool,
+    ) -> Result<Vec<DrawSprite>, AtlasFull> {
+        let sprite = Sprite::from_texture(self.texture.clone());
+        let atlas_size = self.size as f32;
+
+        let mut sprites = Vec::new();
+        for (text, layout) in texts.iter().zip(layouts) {
+            for glyph in &layout.glyphs {
+                let draw = match &*text.font.data {
+                    FontData::Vector(font) => {
+                        let key = (text.font.id, glyph.index as u16, text.size.to_bits());
+                        let cached = match self.glyph(queue, font, key) {
+                            Ok(cached) => cached,
+                            Err(AtlasFull) if skip_overflow => continue,
+                            Err(AtlasFull) => return Err(AtlasFull),
+                        };
+
+                        let min = Vec2::new(cached.x as f32, cached.y as f32);
+                        let size = Vec2::new(cached.width as f32, cached.height as f32);
+                        DrawSprite::new(sprite.clone(), text.position + glyph.rect.min)
+                            .uv(Rect::new(min / atlas_size, (min + size) / atlas_size))
+                    }
+                    FontData::Bitmap(bitmap) => {
+                        let Some(bitmap_glyph) = bitmap.glyphs.get(&glyph.index) else {
+                            continue;
+                        };
+
+                        let page = Sprite::from_texture(bitmap.pages[bitmap_glyph.page].clone());
+                        let scale = Font::bitmap_scale(bitmap, text.size);
+                        // snapped to the texel grid, bitmap fonts fall apart in between
+                        DrawSprite::new(page, (text.position + glyph.rect.min).round())
+                            .uv(bitmap.uv(bitmap_glyph))
+                            .scale(Vec2::splat(scale))
+                    }
+                };
+
+                sprites.push(draw.origin(Vec2::ZERO).tint(text.color).layer(text.layer));
+            }
+        }
+
+        Ok(sprites)
+    }
+
+    fn clear(&mut self) {
+        self.packer.clear();
+        self.glyphs.clear();
+    }
+
+    /// Looks the glyph up in the atlas, rasterizing it if this is the first time it's used
+    fn glyph(
+        &mut self,
+        queue: &wgpu::Queue,
+        font: &fontdue::Font,
+        key: (u64, u16, u32),
+    ) -> Result<AtlasGlyph, AtlasFull> {
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return Ok(*glyph);
+        }
+
+        let (_, index, size) = key;
+        let (metrics, coverage) = font.rasterize_indexed(index, f32::from_bits(size));
+        let (width, height) = (metrics.width as u32, metrics.height as u32);
+        let (x, y) = self.packer.allocate(width, height).ok_or(AtlasFull)?;
+
+        let rgba: Vec<u8> = coverage
+            .iter()
+            .flat_map(|alpha| [255, 255, 255, *alpha])
+            .collect();
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &rgba,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let glyph = AtlasGlyph {
+            x,
+            y,
+            width,
+            height,
+        };
+        self.glyphs.insert(key, glyph);
+        Ok(glyph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font() -> Font {
+        Font::from_bytes(include_bytes!("../../../assets/fonts/DejaVuSans.ttf")).unwrap()
+    }
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    fn width(font: &Font, text: &str) -> f32 {
+        font.measure(&chars(text), 16.0)
+    }
+
+    #[test]
+    fn wraps_at_newlines() {
+        let font = font();
+        let text = chars("ab\n\ncd\n");
+        assert_eq!(wrap(&font, &text, 16.0, None), [0..2, 3..3, 4..6, 7..7]);
+        assert_eq!(
+            wrap(&font, &text, 16.0, Some(1000.0)),
+            [0..2, 3..3, 4..6, 7..7]
+        );
+    }
+
+    #[test]
+    fn wraps_at_words() {
+        let font = font();
+        let text = chars("hello world again");
+
+        // spaces never start a new line, they hang off the end of the one before
+        let max_width = width(&font, "hello world");
+        assert_eq!(wrap(&font, &text, 16.0, Some(max_width)), [0..12, 12..17]);
+
+        let max_width = width(&font, "hello wor");
+        assert_eq!(
+            wrap(&font, &text, 16.0, Some(max_width)),
+            [0..6, 6..12, 12..17]
+        );
+    }
+
+    #[test]
+    fn breaks_up_long_words() {
+        let font = font();
+        let text = chars("abcdefgh");
+        let max_width = width(&font, "abc") + 0.5;
+        assert_eq!(
+            wrap(&font, &text, 16.0, Some(max_width)),
+            [0..3, 3..6, 6..8]
+        );
+
+        // a single glyph wider than the line still goes somewhere
+        assert_eq!(wrap(&font, &chars("ab"), 16.0, Some(1.0)), [0..1, 1..2]);
+    }
+
+    #[test]
+    fn layout_ignores_trailing_whitespace() {
+        let font = font();
+        let text = Text::new(font.clone(), "hi   \nhello", Vec2::ZERO);
+        let layout = text.layout();
+
+        assert_eq!(layout.lines, 2);
+        assert_eq!(layout.glyphs.len(), 7);
+        assert_eq!(layout.bounds.width(), width(&font, "hello"));
+
+        let text = Text::new(font.clone(), "hi   ", Vec2::ZERO);
+        assert_eq!(text.measure().x, width(&font, "hi"));
+    }
+
+    #[test]
+    fn layout_aligns_lines() {
+        let font = font();
+        let line = width(&font, "hi");
+        let text = Text::new(font, "hi", Vec2::ZERO).max_width(100.0);
+
+        let x = |align| text.clone().align(align).layout().glyphs[0].rect.min.x;
+        let left = x(Align::Left);
+        assert!((x(Align::Center) - left - (100.0 - line) * 0.5).abs() <= 0.5);
+        assert!((x(Align::Right) - left - (100.0 - line)).abs() <= 0.5);
+        assert_eq!(text.measure().x, 100.0);
+    }
+
+    #[test]
+    fn layout_stacks_lines() {
+        let font = font();
+        let text = Text::new(font.clone(), "a\na", Vec2::ZERO).line_height(2.0);
+        let layout = text.layout();
+
+        let spacing = font.line_spacing(16.0) * 2.0;
+        let [first, second] = [layout.glyphs[0].rect, layout.glyphs[1].rect];
+        assert_eq!(first.min.x, second.min.x);
+        assert!((first.min.y - second.min.y - spacing).abs() <= 1.0);
+
+        // top left at the position by default, the text hangs below it
+        assert_eq!(layout.bounds.max.y, 0.0);
+        assert_eq!(layout.bounds.min.x, 0.0);
+        let metrics = font.line_metrics(16.0);
+        let height = metrics.ascent - metrics.descent + spacing;
+        assert!((layout.bounds.height() - height).abs() <= 1e-3);
+    }
+
+    #[test]
+    fn kerning() {
+        let font = font();
+        let [a, v] = [font.glyph_index('A'), font.glyph_index('V')];
+        let kern = font.kern(a, v, 16.0);
+        assert!(kern < 0.0);
+
+        let advance = font.glyph_metrics(a, 16.0).advance + font.glyph_metrics(v, 16.0).advance;
+        assert_eq!(width(&font, "AV"), advance + kern);
+
+        // the second glyph moves back by the kerning, give or take rounding
+        let x = |text: &str| {
+            let layout = Text::new(font.clone(), text, Vec2::ZERO).layout();
+            layout.glyphs[1].rect.min.x
+        };
+        let unkerned = x("A V") - font.glyph_metrics(font.glyph_index(' '), 16.0).advance;
+        assert!((x("AV") - unkerned - kern).abs() <= 1.0);
+    }
+}
@@ -0,0 +1,117 @@
+use vge_math::{Rect, Vec2};
+use wgpu::{ShaderModuleDescriptor, include_wgsl};
+
+use crate::target::RenderTarget;
+
+const UPSCALE_SHADER: ShaderModuleDescriptor =
+    include_wgsl!("../../../assets/shaders/upscale.wgsl");
+
+/// Part of a window of `window_size` pixels a pixel-perfect frame of `resolution`
+/// ends up in, centered and scaled by a whole number, or shrunk to fit when
+/// the window is smaller than the resolution
+///
+/// Origin at the top left, like window coordinates
+pub fn screen_rect(window_size: (u32, u32), resolution: (u32, u32)) -> Rect {
+    let window = Vec2::new(window_size.0 as f32, window_size.1 as f32);
+    let resolution = Vec2::new(resolution.0.max(1) as f32, resolution.1.max(1) as f32);
+
+    let fit = (window.x / resolution.x).min(window.y / resolution.y);
+    let scale = if fit >= 1.0 { fit.floor() } else { fit };
+
+    let size = resolution * scale;
+    let min = ((window - size) * 0.5).round();
+    Rect::new(min, min + size)
+}
+
+/// Draws a [`RenderTarget`] onto the window, scaled up by whole numbers when it's smaller
+pub(crate) struct Blit {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Blit {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let shader = device.create_shader_module(UPSCALE_SHADER);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blit pipeline layout"),
+            bind_group_layouts: &[layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Blit pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::all(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self { pipeline }
+    }
+
+    /// Fills `view` with `source` as big as [`screen_rect`] allows, the bars left around it are black
+    pub(crate) fn draw(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        source: &RenderTarget,
+        view: &wgpu::TextureView,
+        window_size: (u32, u32),
+    ) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Blit encoder"),
+        });
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Blit pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let rect = screen_rect(window_size, source.size());
+        if !rect.is_empty() {
+            render_pass.set_viewport(
+                rect.min.x,
+                rect.min.y,
+                rect.width(),
+                rect.height(),
+                0.0,
+                1.0,
+            );
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, source.bind_group(), &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        drop(render_pass);
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+}
@@ -0,0 +1,50 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// How often watched files get checked for changes
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Notices files changing by polling their modification times
+#[derive(Default)]
+pub(crate) struct FileWatcher {
+    /// Last seen modification time, `None` while the file is missing
+    files: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl FileWatcher {
+    /// Starts watching `path` from its current state, does nothing if it's already watched
+    pub(crate) fn watch(&mut self, path: &Path) {
+        if !self.files.contains_key(path) {
+            self.files.insert(path.to_owned(), modified(path));
+        }
+    }
+
+    /// Stops watching everything `keep` returns false for
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&Path) -> bool) {
+        self.files.retain(|path, _| keep(path));
+    }
+
+    /// Files written to since the last call, including ones that showed up again
+    pub(crate) fn changed(&mut self) -> Vec<PathBuf> {
+        self.files
+            .iter_mut()
+            .filter_map(|(path, seen)| {
+                let current = modified(path);
+                if current.is_none() || current == *seen {
+                    return None;
+                }
+                *seen = current;
+                Some(path.clone())
+            })
+            .collect()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,183 @@
+use vge_app::input::{Key, KeyCode, MouseButton, NamedKey};
+use vge_math::Vec2;
+use winit::{event::MouseScrollDelta, keyboard};
+
+/// Roughly how many pixels touchpads scroll for a single wheel notch
+const PIXELS_PER_LINE: f32 = 20.0;
+
+pub(crate) fn key_code(key: keyboard::PhysicalKey) -> Option<KeyCode> {
+    let keyboard::PhysicalKey::Code(code) = key else {
+        return None;
+    };
+
+    use keyboard::KeyCode as K;
+    Some(match code {
+        K::KeyA => KeyCode::KeyA,
+        K::KeyB => KeyCode::KeyB,
+        K::KeyC => KeyCode::KeyC,
+        K::KeyD => KeyCode::KeyD,
+        K::KeyE => KeyCode::KeyE,
+        K::KeyF => KeyCode::KeyF,
+        K::KeyG => KeyCode::KeyG,
+        K::KeyH => KeyCode::KeyH,
+        K::KeyI => KeyCode::KeyI,
+        K::KeyJ => KeyCode::KeyJ,
+        K::KeyK => KeyCode::KeyK,
+        K::KeyL => KeyCode::KeyL,
+        K::KeyM => KeyCode::KeyM,
+        K::KeyN => KeyCode::KeyN,
+        K::KeyO => KeyCode::KeyO,
+        K::KeyP => KeyCode::KeyP,
+        K::KeyQ => KeyCode::KeyQ,
+        K::KeyR => KeyCode::KeyR,
+        K::KeyS => KeyCode::KeyS,
+        K::KeyT => KeyCode::KeyT,
+        K::KeyU => KeyCode::KeyU,
+        K::KeyV => KeyCode::KeyV,
+        K::KeyW => KeyCode::KeyW,
+        K::KeyX => KeyCode::KeyX,
+        K::KeyY => KeyCode::KeyY,
+        K::KeyZ => KeyCode::KeyZ,
+        K::Digit0 => KeyCode::Digit0,
+        K::Digit1 => KeyCode::Digit1,
+        K::Digit2 => KeyCode::Digit2,
+        K::Digit3 => KeyCode::Digit3,
+        K::Digit4 => KeyCode::Digit4,
+        K::Digit5 => KeyCode::Digit5,
+        K::Digit6 => KeyCode::Digit6,
+        K::Digit7 => KeyCode::Digit7,
+        K::Digit8 => KeyCode::Digit8,
+        K::Digit9 => KeyCode::Digit9,
+        K::F1 => KeyCode::F1,
+        K::F2 => KeyCode::F2,
+        K::F3 => KeyCode::F3,
+        K::F4 => KeyCode::F4,
+        K::F5 => KeyCode::F5,
+        K::F6 => KeyCode::F6,
+        K::F7 => KeyCode::F7,
+        K::F8 => KeyCode::F8,
+        K::F9 => KeyCode::F9,
+        K::F10 => KeyCode::F10,
+        K::F11 => KeyCode::F11,
+        K::F12 => KeyCode::F12,
+        K::Escape => KeyCode::Escape,
+        K::Enter => KeyCode::Enter,
+        K::Tab => KeyCode::Tab,
+        K::Backspace => KeyCode::Backspace,
+        K::Space => KeyCode::Space,
+        K::Insert => KeyCode::Insert,
+        K::Delete => KeyCode::Delete,
+        K::Home => KeyCode::Home,
+        K::End => KeyCode::End,
+        K::PageUp => KeyCode::PageUp,
+        K::PageDown => KeyCode::PageDown,
+        K::ArrowUp => KeyCode::ArrowUp,
+        K::ArrowDown => KeyCode::ArrowDown,
+        K::ArrowLeft => KeyCode::ArrowLeft,
+        K::ArrowRight => KeyCode::ArrowRight,
+        K::ShiftLeft => KeyCode::ShiftLeft,
+        K::ShiftRight => KeyCode::ShiftRight,
+        K::ControlLeft => KeyCode::ControlLeft,
+        K::ControlRight => KeyCode::ControlRight,
+        K::AltLeft => KeyCode::AltLeft,
+        K::AltRight => KeyCode::AltRight,
+        K::SuperLeft => KeyCode::SuperLeft,
+        K::SuperRight => KeyCode::SuperRight,
+        K::CapsLock => KeyCode::CapsLock,
+        K::Minus => KeyCode::Minus,
+        K::Equal => KeyCode::Equal,
+        K::BracketLeft => KeyCode::BracketLeft,
+        K::BracketRight => KeyCode::BracketRight,
+        K::Backslash => KeyCode::Backslash,
+        K::Semicolon => KeyCode::Semicolon,
+        K::Quote => KeyCode::Quote,
+        K::Backquote => KeyCode::Backquote,
+        K::Comma => KeyCode::Comma,
+        K::Period => KeyCode::Period,
+        K::Slash => KeyCode::Slash,
+        K::Numpad0 => KeyCode::Numpad0,
+        K::Numpad1 => KeyCode::Numpad1,
+        K::Numpad2 => KeyCode::Numpad2,
+        K::Numpad3 => KeyCode::Numpad3,
+        K::Numpad4 => KeyCode::Numpad4,
+        K::Numpad5 => KeyCode::Numpad5,
+        K::Numpad6 => KeyCode::Numpad6,
+        K::Numpad7 => KeyCode::Numpad7,
+        K::Numpad8 => KeyCode::Numpad8,
+        K::Numpad9 => KeyCode::Numpad9,
+        K::NumpadAdd => KeyCode::NumpadAdd,
+        K::NumpadSubtract => KeyCode::NumpadSubtract,
+        K::NumpadMultiply => KeyCode::NumpadMultiply,
+        K::NumpadDivide => KeyCode::NumpadDivide,
+        K::NumpadDecimal => KeyCode::NumpadDecimal,
+        K::NumpadEnter => KeyCode::NumpadEnter,
+        _ => return None,
+    })
+}
+
+pub(crate) fn key(key: &keyboard::Key) -> Option<Key> {
+    use keyboard::NamedKey as N;
+    let named = match key {
+        keyboard::Key::Character(text) => return text.chars().next().map(Key::character),
+        keyboard::Key::Named(named) => named,
+        _ => return None,
+    };
+
+    Some(Key::Named(match named {
+        N::Escape => NamedKey::Escape,
+        N::Enter => NamedKey::Enter,
+        N::Tab => NamedKey::Tab,
+        N::Backspace => NamedKey::Backspace,
+        N::Space => NamedKey::Space,
+        N::Insert => NamedKey::Insert,
+        N::Delete => NamedKey::Delete,
+        N::Home => NamedKey::Home,
+        N::End => NamedKey::End,
+        N::PageUp => NamedKey::PageUp,
+        N::PageDown => NamedKey::PageDown,
+        N::ArrowUp => NamedKey::ArrowUp,
+        N::ArrowDown => NamedKey::ArrowDown,
+        N::ArrowLeft => NamedKey::ArrowLeft,
+        N::ArrowRight => NamedKey::ArrowRight,
+        N::Shift => NamedKey::Shift,
+        N::Control => NamedKey::Control,
+        N::Alt => NamedKey::Alt,
+        N::Super => NamedKey::Super,
+        N::CapsLock => NamedKey::CapsLock,
+        N::F1 => NamedKey::F1,
+        N::F2 => NamedKey::F2,
+        N::F3 => NamedKey::F3,
+        N::F4 => NamedKey::F4,
+        N::F5 => NamedKey::F5,
+        N::F6 => NamedKey::F6,
+        N::F7 => NamedKey::F7,
+        N::F8 => NamedKey::F8,
+        N::F9 => NamedKey::F9,
+        N::F10 => NamedKey::F10,
+        N::F11 => NamedKey::F11,
+        N::F12 => NamedKey::F12,
+        _ => return None,
+    }))
+}
+
+pub(crate) fn mouse_button(button: winit::event::MouseButton) -> MouseButton {
+    match button {
+        winit::event::MouseButton::Left => MouseButton::Left,
+        winit::event::MouseButton::Right => MouseButton::Right,
+        winit::event::MouseButton::Middle => MouseButton::Middle,
+        winit::event::MouseButton::Back => MouseButton::Back,
+        winit::event::MouseButton::Forward => MouseButton::Forward,
+        winit::event::MouseButton::Other(id) => MouseButton::Other(id),
+    }
+}
+
+/// Scroll delta in lines
+pub(crate) fn scroll_delta(delta: MouseScrollDelta) -> Vec2 {
+    match delta {
+        MouseScrollDelta::LineDelta(x, y) => Vec2::new(x, y),
+        MouseScrollDelta::PixelDelta(pos) => Vec2::new(
+            pos.x as f32 / PIXELS_PER_LINE,
+            pos.y as f32 / PIXELS_PER_LINE,
+        ),
+    }
+}
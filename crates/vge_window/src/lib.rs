@@ -1,20 +1,34 @@
-use std::sync::{Arc, mpsc};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex, mpsc},
+    thread::JoinHandle,
+};
 
 use thiserror::Error;
-use tracing::info;
-use vge_app::{App, Ctx};
-use vge_render::Gfx;
+use tracing::{error, info, warn};
+use vge_app::{
+    App, Command, Ctx,
+    input::InputState,
+    options::{Fullscreen, Options, Renderer, WindowConfig},
+};
+use vge_math::Vec2;
+use vge_render::{Frame, Gfx, RenderError, SurfaceError};
 use winit::{
-    application::ApplicationHandler, error::EventLoopError, event::WindowEvent,
+    application::ApplicationHandler,
+    dpi::{LogicalSize, PhysicalSize},
+    error::EventLoopError,
+    event::{ElementState, WindowEvent},
     event_loop::ControlFlow,
 };
 
+mod input;
+
 pub trait Window {
     fn size(&self) -> (u32, u32);
 }
 
-pub fn winit<'a, A: App>(size: (u32, u32), app: A) -> Result<WindowBackend<'a, A>, WindowError> {
-    let winit = WinitWindow::new(size, app)?;
+pub fn winit<'a, A: App>(options: Options, app: A) -> Result<WindowBackend<'a, A>, WindowError> {
+    let winit = WinitWindow::new(options, app)?;
     Ok(WindowBackend::Winit(winit))
 }
 
@@ -32,11 +46,22 @@ impl<A: App> WindowBackend<'_, A> {
 
 pub struct WinitWindow<'a, A: App> {
     pub size: (u32, u32),
+    pub options: Options,
     pub gfx: Option<Gfx<'a>>,
     pub window: Option<Arc<winit::window::Window>>,
-    pub draw_receiver: mpsc::Receiver<i32>,
-    pub draw_sender: mpsc::Sender<i32>,
+    pub draw_receiver: mpsc::Receiver<Command>,
+    pub draw_sender: mpsc::Sender<Command>,
     pub app: Option<A>,
+    /// Last complete frame sent by the game thread
+    pub frame: Frame,
+    /// Frame currently being built by the game thread
+    pub pending: Frame,
+    /// Where to save the next frame once it's rendered
+    pub screenshots: Vec<PathBuf>,
+    /// Wakes the game thread up to draw, dropping it stops the game loop
+    pub redraw_sender: Option<mpsc::Sender<()>>,
+    pub game_thread: Option<JoinHandle<()>>,
+    pub input_state: Arc<Mutex<InputState>>,
 }
 
 impl<A: App> Window for WinitWindow<'_, A> {
@@ -46,18 +71,160 @@ impl<A: App> Window for WinitWindow<'_, A> {
 }
 
 impl<A: App> WinitWindow<'_, A> {
-    fn new(size: (u32, u32), app: A) -> Result<Self, WindowError> {
+    fn new(options: Options, app: A) -> Result<Self, WindowError> {
         let (draw_sender, draw_receiver) = std::sync::mpsc::channel();
         Ok(Self {
             window: None,
-            size,
+            size: options.window_config.size,
+            options,
             gfx: None,
             draw_receiver,
             draw_sender,
             app: Some(app),
+            frame: Frame::new(),
+            pending: Frame::new(),
+            screenshots: Vec::new(),
+            redraw_sender: None,
+            game_thread: None,
+            input_state: Default::default(),
         })
     }
 
+    fn update_input(&self, f: impl FnOnce(&mut InputState)) {
+        if let Ok(mut state) = self.input_state.lock() {
+            f(&mut state);
+        }
+    }
+
+    fn shutdown(&mut self) {
+        self.redraw_sender = None;
+        if let Some(game_thread) = self.game_thread.take()
+            && game_thread.join().is_err()
+        {
+            error!("Game thread panicked!");
+        }
+    }
+
+    fn handle_commands(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        for command in self.draw_receiver.try_iter() {
+            match command {
+                Command::DrawSprite(sprite) => self.pending.sprites.push(sprite),
+                Command::DrawShapes(painter) => self.pending.painter.append(painter),
+                Command::DrawText(text) => self.pending.texts.push(text),
+                Command::DrawToTarget(draw) => self.pending.targets.push(draw),
+                Command::SetClearColor(color) => {
+                    if let Some(gfx) = &mut self.gfx {
+                        gfx.set_clear_color(color);
+                    }
+                }
+                Command::SetFrameClearColor(color) => self.pending.clear_color = Some(color),
+                Command::SetPixelPerfect(resolution) => {
+                    if let Some(gfx) = &mut self.gfx {
+                        gfx.set_pixel_perfect(resolution);
+                    }
+                }
+                Command::SetViewports(viewports) => {
+                    if let Some(gfx) = &mut self.gfx {
+                        gfx.set_viewports(viewports);
+                    }
+                }
+                Command::Screenshot(path) => {
+                    if let Some(gfx) = &mut self.gfx {
+                        gfx.capture_next_frame();
+                        self.screenshots.push(path);
+                    }
+                }
+                Command::StartRecording(dir, fps) => {
+                    if let Some(gfx) = &mut self.gfx
+                        && let Err(err) = gfx.start_recording(&dir, fps)
+                    {
+                        error!("Could not record to {}: {err}", dir.display());
+                    }
+                }
+                Command::StopRecording => {
+                    if let Some(gfx) = &mut self.gfx
+                        && let Err(err) = gfx.stop_recording()
+                    {
+                        error!("Could not finish recording: {err}");
+                    }
+                }
+                Command::SetTitle(title) => {
+                    if let Some(window) = &self.window {
+                        window.set_title(&title);
+                    }
+                }
+                Command::SetVisible(visible) => {
+                    if let Some(window) = &self.window {
+                        window.set_visible(visible);
+                    }
+                }
+                Command::Resize(width, height) => {
+                    if let Some(window) = &self.window {
+                        let _ = window.request_inner_size(PhysicalSize::new(width, height));
+                    }
+                }
+                Command::Quit => {
+                    info!("Quit was requested!");
+                    event_loop.exit();
+                }
+                Command::Present => {
+                    self.frame = std::mem::take(&mut self.pending);
+                }
+            }
+        }
+    }
+
+    fn window_attributes(
+        config: &WindowConfig,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+    ) -> winit::window::WindowAttributes {
+        let mut attributes = winit::window::Window::default_attributes()
+            .with_title(&config.title)
+            .with_inner_size(LogicalSize::new(config.size.0, config.size.1))
+            .with_resizable(config.resizable)
+            .with_decorations(config.decorations)
+            .with_visible(config.visible);
+
+        if let Some((width, height)) = config.min_size {
+            attributes = attributes.with_min_inner_size(LogicalSize::new(width, height));
+        }
+
+        if let Some((width, height)) = config.max_size {
+            attributes = attributes.with_max_inner_size(LogicalSize::new(width, height));
+        }
+
+        if let Some(icon) = &config.icon {
+            match winit::window::Icon::from_rgba(icon.as_raw().clone(), icon.width(), icon.height())
+            {
+                Ok(icon) => attributes = attributes.with_window_icon(Some(icon)),
+                Err(err) => error!("Invalid window icon: {err}"),
+            }
+        }
+
+        let fullscreen = match config.fullscreen {
+            Fullscreen::Windowed => None,
+            Fullscreen::Borderless => Some(winit::window::Fullscreen::Borderless(None)),
+            Fullscreen::Exclusive => {
+                let video_mode = event_loop
+                    .primary_monitor()
+                    .or_else(|| event_loop.available_monitors().next())
+                    .and_then(|monitor| {
+                        monitor.video_modes().max_by_key(|mode| {
+                            let size = mode.size();
+                            (size.width * size.height, mode.refresh_rate_millihertz())
+                        })
+                    });
+
+                match video_mode {
+                    Some(mode) => Some(winit::window::Fullscreen::Exclusive(mode)),
+                    None => Some(winit::window::Fullscreen::Borderless(None)),
+                }
+            }
+        };
+
+        attributes.with_fullscreen(fullscreen)
+    }
+
     pub(crate) fn run(&mut self) -> Result<(), WindowError> {
         let event_loop = winit::event_loop::EventLoop::new()?;
         event_loop.set_control_flow(ControlFlow::Poll);
@@ -72,21 +239,35 @@ impl<A: App> WinitWindow<'_, A> {
 
 impl<A: App> ApplicationHandler for WinitWindow<'_, A> {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        let window_attributes = winit::window::Window::default_attributes()
-            .with_title("forsen")
-            .with_visible(true);
+        let window_attributes = Self::window_attributes(&self.options.window_config, event_loop);
 
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
-        let mut gfx = vge_render::wgpu(window.clone(), self.size).unwrap();
-        gfx.set_surface_size(window.inner_size().width, window.inner_size().height);
+        let size = window.inner_size();
+        self.size = (size.width, size.height);
+
+        let mut gfx = match self.options.renderer {
+            Renderer::Wgpu => vge_render::wgpu(window.clone(), self.size).unwrap(),
+        };
+        gfx.set_present_mode(self.options.window_config.present_mode);
+        gfx.assets().set_root(&self.options.asset_root);
+        gfx.set_hot_reload(self.options.hot_reload);
+        gfx.set_pixel_perfect(self.options.pixel_perfect);
+        gfx.set_surface_size(size.width, size.height);
+        self.update_input(|input| input.resized(size.width, size.height));
 
         if let Some(mut app) = self.app.take() {
-            let mut ctx = Ctx::new(self.draw_sender.clone());
+            let mut ctx = Ctx::new(self.draw_sender.clone(), self.input_state.clone());
+            ctx.set_tick_rate(self.options.tick_rate);
+            ctx.sync_with(&gfx);
             app.init(&mut ctx, &mut gfx);
+            // init may have set cameras on gfx directly, mouse_world needs them
+            ctx.sync_with(&gfx);
 
-            std::thread::spawn(move || {
-                app.step(&mut ctx);
-            });
+            let (redraw_sender, redraw_receiver) = mpsc::channel();
+            self.redraw_sender = Some(redraw_sender);
+            self.game_thread = Some(std::thread::spawn(move || {
+                vge_app::game_loop::run(&mut app, &mut ctx, redraw_receiver);
+            }));
         }
 
         self.gfx = Some(gfx);
@@ -96,7 +277,7 @@ impl<A: App> ApplicationHandler for WinitWindow<'_, A> {
     fn window_event(
         &mut self,
         event_loop: &winit::event_loop::ActiveEventLoop,
-        window_id: winit::window::WindowId,
+        _window_id: winit::window::WindowId,
         event: winit::event::WindowEvent,
     ) {
         match event {
@@ -106,22 +287,90 @@ impl<A: App> ApplicationHandler for WinitWindow<'_, A> {
             }
             WindowEvent::RedrawRequested => {
                 self.window.as_ref().unwrap().request_redraw();
+                if let Some(redraw_sender) = &self.redraw_sender {
+                    let _ = redraw_sender.send(());
+                }
+                self.handle_commands(event_loop);
+
                 let Some(gfx) = &mut self.gfx else {
                     return;
                 };
 
-                gfx.render(&[]).unwrap();
+                // the same frame gets rendered again until the game presents a new one,
+                // drawing into targets twice would blend over what's there twice
+                for draw in std::mem::take(&mut self.frame.targets) {
+                    gfx.render_to(&draw);
+                }
+
+                match gfx.render(&self.frame) {
+                    Ok(()) => (),
+                    // the surface needs configuring again after resizes and monitor changes
+                    Err(RenderError::Surface(SurfaceError::Outdated | SurfaceError::Lost)) => {
+                        gfx.set_surface_size(self.size.0, self.size.1);
+                        return;
+                    }
+                    Err(RenderError::Surface(SurfaceError::Timeout)) => {
+                        warn!("Timed out waiting for the next frame, skipping it");
+                        return;
+                    }
+                    Err(RenderError::Surface(SurfaceError::OutOfMemory)) => {
+                        error!("Ran out of memory while rendering, exiting");
+                        event_loop.exit();
+                        return;
+                    }
+                    Err(err) => {
+                        error!("Could not render frame: {err}");
+                        return;
+                    }
+                }
+
+                for path in self.screenshots.drain(..) {
+                    if let Err(err) = gfx.save_screenshot(&path) {
+                        error!("Could not save screenshot to {}: {err}", path.display());
+                    }
+                }
             }
             WindowEvent::Resized(size) => {
+                self.size = (size.width, size.height);
+                self.update_input(|input| input.resized(size.width, size.height));
+
                 let Some(gfx) = &mut self.gfx else {
                     return;
                 };
 
                 gfx.set_surface_size(size.width, size.height);
             }
+            WindowEvent::KeyboardInput { event, .. } => {
+                if event.repeat {
+                    return;
+                }
+
+                let code = input::key_code(event.physical_key);
+                let key = input::key(&event.logical_key);
+                let pressed = event.state == ElementState::Pressed;
+                self.update_input(|input| input.key(code, key, pressed));
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let button = input::mouse_button(button);
+                let pressed = state == ElementState::Pressed;
+                self.update_input(|input| input.mouse_button(button, pressed));
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = Vec2::new(position.x as f32, position.y as f32);
+                self.update_input(|input| input.cursor_moved(position));
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = input::scroll_delta(delta);
+                self.update_input(|input| input.scroll(delta));
+            }
+            WindowEvent::Focused(false) => self.update_input(InputState::focus_lost),
             _ => (),
         }
     }
+
+    fn exiting(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        self.shutdown();
+    }
 }
 
 #[derive(Error, Debug)]
@@ -0,0 +1,32 @@
+use vge::prelude::*;
+
+fn main() {
+    tracing_subscriber::fmt::init();
+
+    let mut gfx = vge_render::headless((640, 480)).unwrap();
+    let logo = gfx.create_sprite("assets/images/vge_logo_9x.png").unwrap();
+    let font = Font::load("assets/fonts/DejaVuSans.ttf").unwrap();
+
+    let mut frame = Frame::new();
+    frame
+        .sprites
+        .push(logo.at(Vec2::ZERO).scale(Vec2::splat(0.25)));
+    frame
+        .painter
+        .rounded_rect_outline(
+            Rect::from_center_size(Vec2::ZERO, Vec2::new(400.0, 300.0)),
+            24.0,
+            4.0,
+            Color::WHITE,
+        )
+        .circle(Vec2::new(-250.0, 180.0), 30.0, Color::new(1.0, 0.4, 0.2));
+
+    frame.texts.push(
+        font.text("Rendered without a window", Vec2::new(0.0, -170.0))
+            .size(24.0)
+            .origin(Vec2::splat(0.5)),
+    );
+
+    gfx.render(&frame).unwrap();
+    gfx.save_screenshot("headless.png").unwrap();
+}
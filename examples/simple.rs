@@ -3,18 +3,79 @@ use vge::prelude::*;
 fn main() {
     tracing_subscriber::fmt::init();
 
-    vge::run(Simple::default()).unwrap();
+    AppBuilder::new(Simple::default())
+        .title("simple")
+        .size(640, 480)
+        .min_size(320, 240)
+        .asset_root("assets")
+        .hot_reload(cfg!(debug_assertions))
+        .run()
+        .unwrap();
 }
 
 #[derive(Default)]
-pub struct Simple {}
+pub struct Simple {
+    logo: Option<Handle<mesh::Sprite>>,
+    font: Option<Handle<Font>>,
+    position: Vec2,
+    previous: Vec2,
+}
 
 impl App for Simple {
     fn init(&mut self, ctx: &mut Ctx, gfx: &mut Gfx) {
-        // todo!()
+        // both load in the background and show up once they're ready
+        self.logo = Some(gfx.assets().load("images/vge_logo_9x.png"));
+        self.font = Some(gfx.assets().load("fonts/DejaVuSans.ttf"));
+        ctx.set_clear_color(Color::new(0.1, 0.2, 0.3));
     }
 
     fn step(&mut self, ctx: &mut Ctx) {
-        // todo!()
+        if ctx.key_pressed(NamedKey::Escape) {
+            ctx.quit();
+        }
+
+        let mut dir = Vec2::ZERO;
+        if ctx.key_down(KeyCode::ArrowLeft) {
+            dir.x -= 1.0;
+        }
+        if ctx.key_down(KeyCode::ArrowRight) {
+            dir.x += 1.0;
+        }
+        if ctx.key_down(KeyCode::ArrowDown) {
+            dir.y -= 1.0;
+        }
+        if ctx.key_down(KeyCode::ArrowUp) {
+            dir.y += 1.0;
+        }
+
+        if ctx.mouse_pressed(MouseButton::Left) {
+            self.position = ctx.mouse_world();
+        }
+
+        let dir = dir.normalize_or_zero();
+        self.previous = self.position;
+        self.position += dir * 200.0 * ctx.delta();
+    }
+
+    fn draw(&mut self, ctx: &mut Ctx, alpha: f32) {
+        let position = self.previous.lerp(self.position, alpha);
+
+        if let Some(logo) = self.logo.as_ref().and_then(Handle::get) {
+            ctx.draw_sprite_ext(logo.at(position).scale(Vec2::splat(0.25)));
+        }
+
+        if let Some(font) = self.font.as_ref().and_then(Handle::get) {
+            ctx.draw_text(
+                font.text(
+                    "Arrow keys to move, click to teleport",
+                    Vec2::new(-312.0, 232.0),
+                )
+                .layer(1),
+            );
+        }
+
+        let cursor = ctx.mouse_world();
+        ctx.painter()
+            .circle_outline(cursor, 12.0, 2.0, Color::WHITE);
     }
 }
@@ -1,19 +1,141 @@
+use std::path::PathBuf;
+
 use thiserror::Error;
-use vge_app::App;
+use vge_app::{
+    App,
+    options::{self, Fullscreen, Options, WindowConfig},
+};
+use vge_render::{PresentMode, image::RgbaImage};
 use vge_window::WindowError;
 
 pub mod prelude {
+    pub use vge_app::input::*;
     pub use vge_app::options::*;
     pub use vge_app::*;
     pub use vge_math::*;
     pub use vge_render::mesh;
     pub use vge_render::*;
+
+    pub use crate::AppBuilder;
 }
 
 pub fn run(app: impl App) -> Result<(), Error> {
-    let mut window = vge_window::winit((640, 480), app)?;
-    window.run()?;
-    Ok(())
+    AppBuilder::new(app).run()
+}
+
+/// Configures the window and backends before running an [`App`]
+pub struct AppBuilder<A: App> {
+    app: A,
+    options: Options,
+}
+
+impl<A: App> AppBuilder<A> {
+    pub fn new(app: A) -> Self {
+        Self {
+            app,
+            options: Options::default(),
+        }
+    }
+
+    pub fn options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn window_config(mut self, config: WindowConfig) -> Self {
+        self.options.window_config = config;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.options.window_config.title = title.into();
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.options.window_config.size = (width, height);
+        self
+    }
+
+    pub fn min_size(mut self, width: u32, height: u32) -> Self {
+        self.options.window_config.min_size = Some((width, height));
+        self
+    }
+
+    pub fn max_size(mut self, width: u32, height: u32) -> Self {
+        self.options.window_config.max_size = Some((width, height));
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.options.window_config.resizable = resizable;
+        self
+    }
+
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.options.window_config.decorations = decorations;
+        self
+    }
+
+    pub fn fullscreen(mut self, fullscreen: Fullscreen) -> Self {
+        self.options.window_config.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.options.window_config.present_mode = present_mode;
+        self
+    }
+
+    pub fn vsync(self, vsync: bool) -> Self {
+        self.present_mode(if vsync {
+            PresentMode::Vsync
+        } else {
+            PresentMode::Immediate
+        })
+    }
+
+    pub fn icon(mut self, icon: RgbaImage) -> Self {
+        self.options.window_config.icon = Some(icon);
+        self
+    }
+
+    pub fn visible(mut self, visible: bool) -> Self {
+        self.options.window_config.visible = visible;
+        self
+    }
+
+    pub fn tick_rate(mut self, tick_rate: u32) -> Self {
+        self.options.tick_rate = tick_rate;
+        self
+    }
+
+    /// Directory assets are loaded from, the working directory by default
+    pub fn asset_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.options.asset_root = root.into();
+        self
+    }
+
+    /// Reloads changed assets and shaders while running, meant for development
+    pub fn hot_reload(mut self, hot_reload: bool) -> Self {
+        self.options.hot_reload = hot_reload;
+        self
+    }
+
+    /// Draws at `width` by `height` and scales that up to the window by whole numbers,
+    /// for crisp pixel art
+    pub fn pixel_perfect(mut self, width: u32, height: u32) -> Self {
+        self.options.pixel_perfect = Some((width, height));
+        self
+    }
+
+    pub fn run(self) -> Result<(), Error> {
+        let mut window = match self.options.window {
+            options::Window::Winit => vge_window::winit(self.options, self.app)?,
+        };
+        window.run()?;
+        Ok(())
+    }
 }
 
 #[derive(Error, Debug)]